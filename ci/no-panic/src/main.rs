@@ -0,0 +1,118 @@
+//! Checks that the `no-panic` feature leaves no panics in firmware using the
+//! combinators and channels.
+//!
+//! The panic handler calls a function that doesn't exist, so linking fails if
+//! any panic survives optimisation. Build it from this directory with:
+//!
+//! ```text
+//! cargo build --release --target thumbv6m-none-eabi
+//! ```
+
+#![no_std]
+#![no_main]
+
+use core::future::Future;
+use core::hint::black_box;
+use core::pin::pin;
+use core::task::{Context, Poll, Waker};
+
+use woven::channel::broadcast::{Broadcast, Overflow};
+use woven::channel::priority::Priority;
+use woven::{FirstN, Join, JoinLimited, Quorum, Race, RaceSame};
+
+#[panic_handler]
+fn panic(_: &core::panic::PanicInfo) -> ! {
+    extern "C" {
+        fn a_panic_survived_optimisation() -> !;
+    }
+
+    unsafe { a_panic_survived_optimisation() }
+}
+
+struct Single;
+critical_section::set_impl!(Single);
+
+unsafe impl critical_section::Impl for Single {
+    unsafe fn acquire() -> bool {
+        false
+    }
+
+    unsafe fn release(_: bool) {}
+}
+
+fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let mut cx = Context::from_waker(Waker::noop());
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+    }
+}
+
+/// A future that's ready after an unknown number of polls.
+struct Work {
+    id: u32,
+    polls: u32,
+}
+
+fn work(id: u32) -> Work {
+    Work {
+        id,
+        polls: black_box(id),
+    }
+}
+
+impl Future for Work {
+    type Output = u32;
+
+    fn poll(mut self: core::pin::Pin<&mut Self>, _: &mut Context<'_>) -> Poll<u32> {
+        if self.polls == 0 {
+            Poll::Ready(self.id)
+        } else {
+            self.polls -= 1;
+            Poll::Pending
+        }
+    }
+}
+
+/// A [`Work`] that never fails.
+struct Fallible(Work);
+
+impl Future for Fallible {
+    type Output = Result<u32, ()>;
+
+    fn poll(self: core::pin::Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let work = unsafe { self.map_unchecked_mut(|f| &mut f.0) };
+        work.poll(cx).map(Ok)
+    }
+}
+
+static PRIORITY: Priority<u32, 4> = Priority::new();
+static BROADCAST: Broadcast<u32, 4, 2> = Broadcast::new(Overflow::DropOldest);
+
+#[no_mangle]
+extern "C" fn _start() -> ! {
+    loop {
+        let (a, b) = block_on((work(1), work(2)).join());
+        black_box(a + b);
+        black_box(block_on((work(3), work(4)).race()));
+        black_box(block_on((work(5), work(6), work(7)).race_same()));
+        black_box(block_on([work(8), work(9), work(10)].first_n::<2>()));
+        let _ = black_box(block_on(
+            [work(11), work(12), work(13)].map(Fallible).quorum::<2>(),
+        ));
+        black_box(block_on([work(14), work(15), work(16)].join_limited(2)));
+
+        // Only the channels' non-async methods are checked. An `async fn`
+        // panics if it's polled after completing, which the optimiser can't
+        // always rule out.
+        let _ = PRIORITY.try_send(black_box(17));
+        black_box(PRIORITY.try_recv());
+
+        if let Some(mut subscriber) = BROADCAST.subscribe() {
+            let _ = BROADCAST.try_send(black_box(18));
+            let _ = black_box(subscriber.try_recv());
+        }
+    }
+}
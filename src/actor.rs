@@ -0,0 +1,403 @@
+//! A minimal actor: a bounded mailbox whose messages are handled one at a
+//! time by a task loop.
+//!
+//! Messages are sent through an [`Address`], either without waiting for the
+//! actor ([`send`](Address::send)) or waiting for its reply
+//! ([`call`](Address::call)). The handler's state lives in the task loop, so it
+//! needs no locking.
+//!
+//! ```rust
+//! use woven::actor::Actor;
+//! use woven::Join;
+//!
+//! enum Counter {
+//!     Add(u32),
+//!     Get,
+//! }
+//!
+//! static COUNTER: Actor<Counter, u32, 4> = Actor::new();
+//!
+//! cassette::block_on(async {
+//!     let mut total = 0;
+//!     let actor = COUNTER.run(async |msg| {
+//!         if let Counter::Add(n) = msg {
+//!             total += n;
+//!         }
+//!         total
+//!     });
+//!
+//!     let client = async {
+//!         let counter = COUNTER.address();
+//!         counter.send(Counter::Add(2)).await.unwrap();
+//!         counter.send(Counter::Add(3)).await.unwrap();
+//!         let total = counter.call(Counter::Get).await;
+//!         counter.close();
+//!         total
+//!     };
+//!
+//!     let ((), total) = (actor, client).join().await;
+//!     assert_eq!(total, Ok(5));
+//! });
+//! ```
+
+use core::task::{Context, Poll};
+
+use crate::channel::ring::Ring;
+use crate::sync::{Mutex, WakerSet, WakerSlot};
+
+/// The error returned when messaging an actor that has shut down.
+///
+/// An actor shuts down once it's been closed and has handled every message
+/// already in its mailbox, or when its task loop is dropped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Shutdown;
+
+impl core::fmt::Display for Shutdown {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("actor has shut down")
+    }
+}
+
+impl core::error::Error for Shutdown {}
+
+/// Where a [`call`](Address::call)'s reply is passed back to the caller.
+enum Reply<R> {
+    Free,
+    Waiting(WakerSlot),
+    /// The caller stopped waiting, so the reply should be discarded.
+    Abandoned,
+    Ready(R),
+    /// The actor shut down without replying.
+    Lost,
+}
+
+struct State<M, R, const N: usize> {
+    /// Queued messages, alongside the reply slot of their caller, if any.
+    mailbox: Ring<(M, Option<usize>), N>,
+    replies: [Reply<R>; N],
+    closed: bool,
+    /// Whether the task loop has been started.
+    running: bool,
+    actor: WakerSlot,
+    senders: WakerSet,
+}
+
+impl<M, R, const N: usize> State<M, R, N> {
+    fn close(&mut self) {
+        self.closed = true;
+        self.actor.wake();
+        self.senders.wake();
+    }
+
+    fn try_send(&mut self, msg: M) -> Result<(), M> {
+        if self.closed {
+            return Err(msg);
+        }
+
+        self.mailbox
+            .push_back((msg, None))
+            .map_err(|(msg, _)| msg)?;
+        self.actor.wake();
+        Ok(())
+    }
+}
+
+/// An actor handling messages of type `M` and replying with `R`, with room for
+/// `N` messages in its mailbox.
+pub struct Actor<M, R, const N: usize> {
+    state: Mutex<State<M, R, N>>,
+}
+
+impl<M, R, const N: usize> Actor<M, R, N> {
+    /// Create a new actor with an empty mailbox.
+    ///
+    /// Fails to compile if `N` is zero.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            state: Mutex::new(State {
+                mailbox: Ring::new(),
+                replies: [const { Reply::Free }; N],
+                closed: false,
+                running: false,
+                actor: WakerSlot::new(),
+                senders: WakerSet::new(),
+            }),
+        }
+    }
+
+    /// A handle for sending messages to the actor.
+    pub fn address(&self) -> Address<'_, M, R, N> {
+        Address { actor: self }
+    }
+
+    /// Stop accepting messages. The task loop finishes once it has handled the
+    /// messages already in the mailbox.
+    pub fn close(&self) {
+        self.state.lock(State::close);
+    }
+
+    fn poll_recv(&self, cx: &mut Context<'_>) -> Poll<Option<(M, Option<usize>)>> {
+        self.state.lock(|s| {
+            if let Some(msg) = s.mailbox.pop_front() {
+                s.senders.wake();
+                Poll::Ready(Some(msg))
+            } else if s.closed {
+                Poll::Ready(None)
+            } else {
+                s.actor.register(cx.waker());
+                Poll::Pending
+            }
+        })
+    }
+
+    /// The actor's task loop, passing each message to `handler` in the order
+    /// they were sent and replying with its output.
+    ///
+    /// Completes once the actor has been [`close`](Self::close)d and its
+    /// mailbox is empty. Dropping it shuts the actor down, failing any calls
+    /// still waiting for a reply.
+    ///
+    /// An actor only runs once, calling this again completes immediately
+    /// without handling any messages.
+    ///
+    /// ```rust
+    /// use woven::actor::Actor;
+    /// use woven::{Either, Race};
+    ///
+    /// static ECHO: Actor<u8, u8, 1> = Actor::new();
+    ///
+    /// cassette::block_on(async {
+    ///     let result = (ECHO.run(async |x| x), ECHO.run(async |x| x)).race().await;
+    ///     assert_eq!(result, Either::Second(()));
+    /// });
+    /// ```
+    pub async fn run(&self, mut handler: impl AsyncFnMut(M) -> R) {
+        struct ShutDown<'a, M, R, const N: usize>(&'a Actor<M, R, N>);
+
+        impl<M, R, const N: usize> Drop for ShutDown<'_, M, R, N> {
+            fn drop(&mut self) {
+                self.0.state.lock(State::close);
+
+                // Messages are dropped outside the lock, in case that uses
+                // the actor.
+                while let Some(msg) = self.0.state.lock(|s| s.mailbox.pop_front()) {
+                    drop(msg);
+                }
+
+                self.0.state.lock(|s| {
+                    for reply in &mut s.replies {
+                        match core::mem::replace(reply, Reply::Free) {
+                            Reply::Waiting(mut waker) => {
+                                *reply = Reply::Lost;
+                                waker.wake();
+                            }
+                            other @ (Reply::Ready(_) | Reply::Lost) => *reply = other,
+                            Reply::Free | Reply::Abandoned => {}
+                        }
+                    }
+                });
+            }
+        }
+
+        if self
+            .state
+            .lock(|s| core::mem::replace(&mut s.running, true))
+        {
+            log_event!("actor is already running");
+            return;
+        }
+
+        let _shut_down = ShutDown(self);
+
+        while let Some((msg, caller)) = core::future::poll_fn(|cx| self.poll_recv(cx)).await {
+            let reply = handler(msg).await;
+
+            let Some(caller) = caller else {
+                continue;
+            };
+
+            // A reply nobody is waiting for is handed back, to be dropped
+            // outside the lock.
+            let unwanted = self.state.lock(|s| {
+                let slot = &mut s.replies[caller];
+                match core::mem::replace(slot, Reply::Free) {
+                    Reply::Waiting(mut waker) => {
+                        *slot = Reply::Ready(reply);
+                        waker.wake();
+                        None
+                    }
+                    Reply::Abandoned => {
+                        s.senders.wake();
+                        Some(reply)
+                    }
+                    other @ (Reply::Free | Reply::Ready(_) | Reply::Lost) => {
+                        *slot = other;
+                        Some(reply)
+                    }
+                }
+            });
+            drop(unwanted);
+        }
+
+        log_event!("actor shut down");
+    }
+}
+
+impl<M, R, const N: usize> Default for Actor<M, R, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A handle for sending messages to an [`Actor`].
+pub struct Address<'a, M, R, const N: usize> {
+    actor: &'a Actor<M, R, N>,
+}
+
+impl<M, R, const N: usize> Clone for Address<'_, M, R, N> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<M, R, const N: usize> Copy for Address<'_, M, R, N> {}
+
+impl<M, R, const N: usize> Address<'_, M, R, N> {
+    /// Try to send a message without waiting, discarding the actor's reply.
+    ///
+    /// # Errors
+    ///
+    /// Returns the message back if the mailbox is full or the actor has been
+    /// closed.
+    pub fn try_send(&self, msg: M) -> Result<(), M> {
+        self.actor.state.lock(|s| s.try_send(msg))
+    }
+
+    /// Send a message, waiting for room in the mailbox if necessary, and
+    /// discarding the actor's reply.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Shutdown`] if the actor has been closed.
+    pub async fn send(&self, msg: M) -> Result<(), Shutdown> {
+        let mut msg = Some(msg);
+
+        core::future::poll_fn(|cx| {
+            self.actor.state.lock(|s| {
+                let Some(m) = msg.take() else { impossible!() };
+
+                match s.try_send(m) {
+                    Ok(()) => Poll::Ready(Ok(())),
+                    Err(_) if s.closed => Poll::Ready(Err(Shutdown)),
+                    Err(m) => {
+                        msg = Some(m);
+                        s.senders.register(cx.waker());
+                        Poll::Pending
+                    }
+                }
+            })
+        })
+        .await
+    }
+
+    /// Send a message, waiting for room in the mailbox if necessary, then wait
+    /// for the actor's reply.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Shutdown`] if the actor has been closed, or if its task loop
+    /// was dropped before it replied.
+    pub async fn call(&self, msg: M) -> Result<R, Shutdown> {
+        /// Frees the reply slot if the caller stops waiting.
+        struct Abandon<'a, M, R, const N: usize> {
+            actor: &'a Actor<M, R, N>,
+            slot: Option<usize>,
+        }
+
+        impl<M, R, const N: usize> Drop for Abandon<'_, M, R, N> {
+            fn drop(&mut self) {
+                let Some(slot) = self.slot else {
+                    return;
+                };
+
+                // A reply that already arrived is handed back, to be dropped
+                // outside the lock.
+                let unwanted = self.actor.state.lock(|s| {
+                    let reply = &mut s.replies[slot];
+                    if let Reply::Waiting(_) = reply {
+                        core::mem::replace(reply, Reply::Abandoned)
+                    } else {
+                        let reply = core::mem::replace(reply, Reply::Free);
+                        s.senders.wake();
+                        reply
+                    }
+                });
+                drop(unwanted);
+            }
+        }
+
+        let mut msg = Some(msg);
+        let mut abandon = Abandon {
+            actor: self.actor,
+            slot: None,
+        };
+
+        core::future::poll_fn(|cx| {
+            self.actor.state.lock(|s| {
+                if let Some(slot) = abandon.slot {
+                    return match core::mem::replace(&mut s.replies[slot], Reply::Free) {
+                        Reply::Ready(reply) => {
+                            abandon.slot = None;
+                            s.senders.wake();
+                            Poll::Ready(Ok(reply))
+                        }
+                        Reply::Lost => {
+                            abandon.slot = None;
+                            s.senders.wake();
+                            Poll::Ready(Err(Shutdown))
+                        }
+                        Reply::Waiting(mut waker) => {
+                            waker.register(cx.waker());
+                            s.replies[slot] = Reply::Waiting(waker);
+                            Poll::Pending
+                        }
+                        Reply::Free | Reply::Abandoned => {
+                            abandon.slot = None;
+                            Poll::Ready(Err(Shutdown))
+                        }
+                    };
+                }
+
+                if s.closed {
+                    return Poll::Ready(Err(Shutdown));
+                }
+
+                let free = s.replies.iter().position(|r| matches!(r, Reply::Free));
+                let (Some(slot), false) = (free, s.mailbox.is_full()) else {
+                    s.senders.register(cx.waker());
+                    return Poll::Pending;
+                };
+
+                let Some(m) = msg.take() else { impossible!() };
+                if s.mailbox.push_back((m, Some(slot))).is_err() {
+                    impossible!();
+                }
+
+                let mut waker = WakerSlot::new();
+                waker.register(cx.waker());
+                s.replies[slot] = Reply::Waiting(waker);
+                abandon.slot = Some(slot);
+                s.actor.wake();
+                Poll::Pending
+            })
+        })
+        .await
+    }
+
+    /// Stop the actor accepting messages. See [`Actor::close`].
+    pub fn close(&self) {
+        self.actor.close();
+    }
+}
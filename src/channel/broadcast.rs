@@ -0,0 +1,276 @@
+//! A broadcast channel, where every subscriber receives every message.
+//!
+//! ```rust
+//! use woven::channel::broadcast::{Broadcast, Overflow};
+//!
+//! static EVENTS: Broadcast<u32, 4, 2> = Broadcast::new(Overflow::Wait);
+//!
+//! cassette::block_on(async {
+//!     let mut a = EVENTS.subscribe().unwrap();
+//!     let mut b = EVENTS.subscribe().unwrap();
+//!
+//!     EVENTS.send(1).await;
+//!     EVENTS.send(2).await;
+//!
+//!     assert_eq!(a.recv().await, Ok(1));
+//!     assert_eq!(a.recv().await, Ok(2));
+//!     assert_eq!(b.recv().await, Ok(1));
+//!     assert_eq!(b.recv().await, Ok(2));
+//! });
+//! ```
+
+use core::pin::Pin;
+use core::task::{Context, Poll};
+
+pub use super::Lagged;
+
+use super::ring::Ring;
+use crate::stream::Stream;
+use crate::sync::{Mutex, WakerSet, WakerSlot};
+
+/// What a [`Broadcast`] does when a message is sent while its buffer is full.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Overflow {
+    /// Sending waits until the slowest subscriber has caught up.
+    Wait,
+    /// The oldest message is discarded to make room. Subscribers that hadn't
+    /// seen it yet will get a [`Lagged`] error on their next receive.
+    DropOldest,
+}
+
+/// The error returned by [`Subscriber::try_recv`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum TryRecvError {
+    /// There are no new messages.
+    Empty,
+    /// See [`Lagged`].
+    Lagged(u64),
+}
+
+struct State<T, const CAP: usize, const SUBS: usize> {
+    /// Buffered messages, alongside the number of subscribers that have yet to
+    /// receive them.
+    queue: Ring<(T, usize), CAP>,
+    /// The sequence number of the message at the front of the queue.
+    front: u64,
+    /// One entry per subscriber slot, `None` when the slot is free.
+    subscribers: [Option<WakerSlot>; SUBS],
+    senders: WakerSet,
+}
+
+impl<T, const CAP: usize, const SUBS: usize> State<T, CAP, SUBS> {
+    /// Remove messages from the front of the queue that every subscriber has
+    /// already received.
+    fn release(&mut self) {
+        let mut released = false;
+
+        while let Some((_, 0)) = self.queue.get_mut(0) {
+            self.queue.pop_front();
+            self.front += 1;
+            released = true;
+        }
+
+        if released {
+            self.senders.wake();
+        }
+    }
+
+    fn try_send(&mut self, overflow: Overflow, msg: T) -> Result<(), T> {
+        let readers = self.subscribers.iter().filter(|s| s.is_some()).count();
+        if readers == 0 {
+            return Ok(());
+        }
+
+        if self.queue.is_full() {
+            match overflow {
+                Overflow::Wait => return Err(msg),
+                Overflow::DropOldest => {
+                    self.queue.pop_front();
+                    self.front += 1;
+                }
+            }
+        }
+
+        if self.queue.push_back((msg, readers)).is_err() {
+            impossible!();
+        }
+
+        for waker in self.subscribers.iter_mut().flatten() {
+            waker.wake();
+        }
+
+        Ok(())
+    }
+}
+
+/// A broadcast channel with room for `CAP` buffered messages and up to `SUBS`
+/// simultaneous subscribers.
+///
+/// Every message sent is delivered to every subscriber that existed at the time
+/// it was sent. Messages sent while there are no subscribers are discarded.
+pub struct Broadcast<T, const CAP: usize, const SUBS: usize> {
+    overflow: Overflow,
+    state: Mutex<State<T, CAP, SUBS>>,
+}
+
+impl<T, const CAP: usize, const SUBS: usize> Broadcast<T, CAP, SUBS> {
+    /// Create a new, empty channel with the given overflow policy.
+    ///
+    /// Fails to compile if `CAP` is zero.
+    #[must_use]
+    pub const fn new(overflow: Overflow) -> Self {
+        const { assert!(CAP > 0, "a `Broadcast` must have a non-zero capacity") };
+
+        Self {
+            overflow,
+            state: Mutex::new(State {
+                queue: Ring::new(),
+                front: 0,
+                subscribers: [const { None }; SUBS],
+                senders: WakerSet::new(),
+            }),
+        }
+    }
+
+    /// Subscribe to the channel, receiving every message sent from now on.
+    ///
+    /// Returns `None` if there are already `SUBS` subscribers.
+    pub fn subscribe(&self) -> Option<Subscriber<'_, T, CAP, SUBS>> {
+        self.state.lock(|s| {
+            let id = s.subscribers.iter().position(Option::is_none)?;
+            s.subscribers[id] = Some(WakerSlot::new());
+
+            Some(Subscriber {
+                channel: self,
+                id,
+                next: s.front + s.queue.len() as u64,
+            })
+        })
+    }
+
+    /// Try to send a message without waiting.
+    ///
+    /// # Errors
+    ///
+    /// Returns the message back if the buffer is full and the channel uses
+    /// [`Overflow::Wait`].
+    pub fn try_send(&self, msg: T) -> Result<(), T> {
+        self.state.lock(|s| s.try_send(self.overflow, msg))
+    }
+
+    /// Send a message, waiting for room in the buffer if necessary.
+    pub async fn send(&self, msg: T) {
+        let mut msg = Some(msg);
+
+        core::future::poll_fn(|cx| {
+            self.state.lock(|s| {
+                let Some(m) = msg.take() else { impossible!() };
+
+                match s.try_send(self.overflow, m) {
+                    Ok(()) => Poll::Ready(()),
+                    Err(m) => {
+                        msg = Some(m);
+                        s.senders.register(cx.waker());
+                        Poll::Pending
+                    }
+                }
+            })
+        })
+        .await;
+    }
+}
+
+/// A subscription to a [`Broadcast`] channel.
+pub struct Subscriber<'a, T, const CAP: usize, const SUBS: usize> {
+    channel: &'a Broadcast<T, CAP, SUBS>,
+    id: usize,
+    /// The sequence number of the next message to receive.
+    next: u64,
+}
+
+impl<T: Clone, const CAP: usize, const SUBS: usize> Subscriber<'_, T, CAP, SUBS> {
+    fn recv_locked(&mut self, s: &mut State<T, CAP, SUBS>) -> Result<T, TryRecvError> {
+        if self.next < s.front {
+            let missed = s.front - self.next;
+            self.next = s.front;
+            return Err(TryRecvError::Lagged(missed));
+        }
+
+        let Some((msg, remaining)) = usize::try_from(self.next - s.front)
+            .ok()
+            .and_then(|i| s.queue.get_mut(i))
+        else {
+            return Err(TryRecvError::Empty);
+        };
+
+        let msg = msg.clone();
+        *remaining -= 1;
+        self.next += 1;
+        s.release();
+
+        Ok(msg)
+    }
+
+    fn poll_recv(&mut self, cx: &mut Context<'_>) -> Poll<Result<T, Lagged>> {
+        let channel = self.channel;
+        channel.state.lock(|s| match self.recv_locked(s) {
+            Ok(msg) => Poll::Ready(Ok(msg)),
+            Err(TryRecvError::Lagged(missed)) => Poll::Ready(Err(Lagged(missed))),
+            Err(TryRecvError::Empty) => {
+                if let Some(waker) = &mut s.subscribers[self.id] {
+                    waker.register(cx.waker());
+                }
+                Poll::Pending
+            }
+        })
+    }
+
+    /// Try to receive the next message without waiting.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TryRecvError::Empty`] if there are no new messages, or
+    /// [`TryRecvError::Lagged`] if messages were dropped before this subscriber
+    /// could receive them.
+    pub fn try_recv(&mut self) -> Result<T, TryRecvError> {
+        let channel = self.channel;
+        channel.state.lock(|s| self.recv_locked(s))
+    }
+
+    /// Receive the next message, waiting for one to be sent if necessary.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Lagged`] if messages were dropped before this subscriber could
+    /// receive them.
+    pub async fn recv(&mut self) -> Result<T, Lagged> {
+        core::future::poll_fn(|cx| self.poll_recv(cx)).await
+    }
+}
+
+/// Yields every message sent to the channel, or [`Lagged`] if some were missed.
+/// Never ends.
+impl<T: Clone, const CAP: usize, const SUBS: usize> Stream for Subscriber<'_, T, CAP, SUBS> {
+    type Item = Result<T, Lagged>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.poll_recv(cx).map(Some)
+    }
+}
+
+impl<T, const CAP: usize, const SUBS: usize> Drop for Subscriber<'_, T, CAP, SUBS> {
+    fn drop(&mut self) {
+        self.channel.state.lock(|s| {
+            let mut index = usize::try_from(self.next.saturating_sub(s.front)).unwrap_or(CAP);
+            while let Some((_, remaining)) = s.queue.get_mut(index) {
+                *remaining -= 1;
+                index += 1;
+            }
+
+            s.subscribers[self.id] = None;
+            s.release();
+        });
+    }
+}
@@ -0,0 +1,73 @@
+//! Channels for communicating between concurrent tasks.
+//!
+//! All channels are statically sized, so they can be placed in a `static` and
+//! shared without an allocator.
+//!
+//! Receiving ends implement [`Stream`](crate::stream::Stream), and their
+//! receive methods return plain futures, so they slot straight into the
+//! combinators. For example, waiting for a message or a shutdown signal:
+//!
+//! ```rust
+//! use woven::channel::priority::Priority;
+//! use woven::channel::watch::Watch;
+//! use woven::stream::StreamExt;
+//! use woven::{Either, Race};
+//!
+//! static MESSAGES: Priority<u32, 4> = Priority::new();
+//! static SHUTDOWN: Watch<bool, 1> = Watch::new(false);
+//!
+//! cassette::block_on(async {
+//!     let mut shutdown = SHUTDOWN.receiver().unwrap();
+//!     let mut messages = &MESSAGES;
+//!
+//!     MESSAGES.try_send(7).unwrap();
+//!     assert_eq!(
+//!         (messages.next(), shutdown.changed()).race().await,
+//!         Either::First(Some(7))
+//!     );
+//!
+//!     SHUTDOWN.send(true);
+//!     assert_eq!(
+//!         (messages.next(), shutdown.changed()).race().await,
+//!         Either::Second(())
+//!     );
+//! });
+//! ```
+
+cfg_locking! {
+    pub mod broadcast;
+    pub mod pipe;
+    pub mod priority;
+    pub mod spsc;
+    pub mod watch;
+}
+
+pub(crate) mod ring;
+
+/// A receiver fell behind and the given number of messages were dropped before
+/// it could receive them.
+///
+/// Returned by [`broadcast`] channels using
+/// [`Overflow::DropOldest`](broadcast::Overflow::DropOldest), where the
+/// subscriber continues from the oldest message still buffered, and by the
+/// outputs of a [`tee`](crate::stream::tee).
+///
+/// ```rust
+/// use woven::channel::broadcast::{Broadcast, Lagged, Overflow};
+///
+/// let channel: Broadcast<u32, 2, 1> = Broadcast::new(Overflow::DropOldest);
+/// let mut sub = channel.subscribe().unwrap();
+///
+/// for i in 0..4 {
+///     channel.try_send(i).unwrap();
+/// }
+///
+/// cassette::block_on(async {
+///     assert_eq!(sub.recv().await, Err(Lagged(2)));
+///     assert_eq!(sub.recv().await, Ok(2));
+///     assert_eq!(sub.recv().await, Ok(3));
+/// });
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Lagged(pub u64);
@@ -0,0 +1,209 @@
+//! An in-memory byte pipe.
+//!
+//! With the `embedded-io` feature enabled, the two ends implement
+//! [`embedded_io_async::Read`] and [`embedded_io_async::Write`], so drivers and
+//! protocol stacks written against those traits can be connected to each other,
+//! or to a test harness.
+//!
+//! ```rust
+//! use woven::channel::pipe::Pipe;
+//!
+//! static PIPE: Pipe<8> = Pipe::new();
+//!
+//! cassette::block_on(async {
+//!     let mut writer = PIPE.writer();
+//!     let mut reader = PIPE.reader();
+//!
+//!     assert_eq!(writer.write(b"hello").await, 5);
+//!
+//!     let mut buf = [0; 8];
+//!     let n = reader.read(&mut buf).await;
+//!     assert_eq!(&buf[..n], b"hello");
+//! });
+//! ```
+
+use core::task::Poll;
+
+use crate::sync::{Mutex, WakerSet};
+
+struct State<const N: usize> {
+    buf: [u8; N],
+    head: usize,
+    len: usize,
+    readers: WakerSet,
+    writers: WakerSet,
+}
+
+impl<const N: usize> State<N> {
+    fn try_write(&mut self, data: &[u8]) -> usize {
+        let count = (N - self.len).min(data.len());
+        for (i, byte) in data[..count].iter().enumerate() {
+            self.buf[(self.head + self.len + i) % N] = *byte;
+        }
+
+        if count > 0 {
+            self.len += count;
+            self.readers.wake();
+        }
+
+        count
+    }
+
+    fn try_read(&mut self, buf: &mut [u8]) -> usize {
+        let count = self.len.min(buf.len());
+        for (i, byte) in buf[..count].iter_mut().enumerate() {
+            *byte = self.buf[(self.head + i) % N];
+        }
+
+        if count > 0 {
+            self.head = (self.head + count) % N;
+            self.len -= count;
+            self.writers.wake();
+        }
+
+        count
+    }
+}
+
+/// A byte pipe with room for `N` buffered bytes.
+pub struct Pipe<const N: usize> {
+    state: Mutex<State<N>>,
+}
+
+impl<const N: usize> Pipe<N> {
+    /// Create a new, empty pipe.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            state: Mutex::new(State {
+                buf: [0; N],
+                head: 0,
+                len: 0,
+                readers: WakerSet::new(),
+                writers: WakerSet::new(),
+            }),
+        }
+    }
+
+    /// Get a handle to the reading end of the pipe.
+    #[must_use]
+    pub fn reader(&self) -> Reader<'_, N> {
+        Reader { pipe: self }
+    }
+
+    /// Get a handle to the writing end of the pipe.
+    #[must_use]
+    pub fn writer(&self) -> Writer<'_, N> {
+        Writer { pipe: self }
+    }
+
+    /// The number of bytes currently buffered.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.state.lock(|s| s.len)
+    }
+
+    /// Whether there are no bytes currently buffered.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<const N: usize> Default for Pipe<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The reading end of a [`Pipe`].
+#[derive(Clone, Copy)]
+pub struct Reader<'a, const N: usize> {
+    pipe: &'a Pipe<N>,
+}
+
+impl<const N: usize> Reader<'_, N> {
+    /// Read as many bytes into `buf` as are available, returning how many were
+    /// read.
+    pub fn try_read(&mut self, buf: &mut [u8]) -> usize {
+        self.pipe.state.lock(|s| s.try_read(buf))
+    }
+
+    /// Read as many bytes into `buf` as are available, waiting until at least
+    /// one can be read. Returns how many were read.
+    ///
+    /// Only returns `0` if `buf` is empty.
+    pub async fn read(&mut self, buf: &mut [u8]) -> usize {
+        core::future::poll_fn(|cx| {
+            self.pipe.state.lock(|s| match s.try_read(buf) {
+                0 if !buf.is_empty() => {
+                    s.readers.register(cx.waker());
+                    Poll::Pending
+                }
+                count => Poll::Ready(count),
+            })
+        })
+        .await
+    }
+}
+
+/// The writing end of a [`Pipe`].
+#[derive(Clone, Copy)]
+pub struct Writer<'a, const N: usize> {
+    pipe: &'a Pipe<N>,
+}
+
+impl<const N: usize> Writer<'_, N> {
+    /// Write as many bytes from `data` as there is room for, returning how many
+    /// were written.
+    pub fn try_write(&mut self, data: &[u8]) -> usize {
+        self.pipe.state.lock(|s| s.try_write(data))
+    }
+
+    /// Write as many bytes from `data` as there is room for, waiting until at
+    /// least one can be written. Returns how many were written.
+    ///
+    /// Only returns `0` if `data` is empty.
+    pub async fn write(&mut self, data: &[u8]) -> usize {
+        core::future::poll_fn(|cx| {
+            self.pipe.state.lock(|s| match s.try_write(data) {
+                0 if !data.is_empty() => {
+                    s.writers.register(cx.waker());
+                    Poll::Pending
+                }
+                count => Poll::Ready(count),
+            })
+        })
+        .await
+    }
+}
+
+#[cfg(feature = "embedded-io")]
+impl<const N: usize> embedded_io_async::ErrorType for Reader<'_, N> {
+    type Error = core::convert::Infallible;
+}
+
+#[cfg(feature = "embedded-io")]
+impl<const N: usize> embedded_io_async::Read for Reader<'_, N> {
+    async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        Ok(Reader::read(self, buf).await)
+    }
+}
+
+#[cfg(feature = "embedded-io")]
+impl<const N: usize> embedded_io_async::ErrorType for Writer<'_, N> {
+    type Error = core::convert::Infallible;
+}
+
+#[cfg(feature = "embedded-io")]
+impl<const N: usize> embedded_io_async::Write for Writer<'_, N> {
+    async fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        Ok(Writer::write(self, buf).await)
+    }
+
+    /// Bytes written to a pipe are immediately available to the reader, so
+    /// there is nothing to flush.
+    async fn flush(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
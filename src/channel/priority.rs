@@ -0,0 +1,188 @@
+//! A channel that always delivers the highest priority message first.
+//!
+//! Message priority is given by their [`Ord`] implementation, with greater
+//! messages received first. Messages that compare equal are received in the
+//! order they were sent.
+//!
+//! ```rust
+//! use woven::channel::priority::Priority;
+//!
+//! #[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+//! enum Message {
+//!     Data(u32),
+//!     Stop,
+//! }
+//!
+//! static CHANNEL: Priority<Message, 4> = Priority::new();
+//!
+//! cassette::block_on(async {
+//!     CHANNEL.send(Message::Data(1)).await;
+//!     CHANNEL.send(Message::Data(1)).await;
+//!     CHANNEL.send(Message::Stop).await;
+//!
+//!     assert_eq!(CHANNEL.recv().await, Message::Stop);
+//!     assert_eq!(CHANNEL.recv().await, Message::Data(1));
+//! });
+//! ```
+
+use core::pin::Pin;
+use core::task::{Context, Poll};
+
+use crate::stream::Stream;
+use crate::sync::{Mutex, WakerSet};
+
+struct State<T, const N: usize> {
+    /// Buffered messages, tagged with the order they were sent in.
+    slots: [Option<(u64, T)>; N],
+    /// The sequence number to give the next message sent.
+    next: u64,
+    senders: WakerSet,
+    receivers: WakerSet,
+}
+
+impl<T: Ord, const N: usize> State<T, N> {
+    fn try_send(&mut self, msg: T) -> Result<(), T> {
+        let Some(slot) = self.slots.iter_mut().find(|s| s.is_none()) else {
+            return Err(msg);
+        };
+
+        *slot = Some((self.next, msg));
+        self.next += 1;
+        self.receivers.wake();
+        Ok(())
+    }
+
+    fn try_recv(&mut self) -> Option<T> {
+        let (index, _) = self
+            .slots
+            .iter()
+            .enumerate()
+            .filter_map(|(i, s)| Some((i, s.as_ref()?)))
+            .max_by(|(_, (a_seq, a)), (_, (b_seq, b))| a.cmp(b).then(b_seq.cmp(a_seq)))?;
+
+        let (_, msg) = self.slots.get_mut(index)?.take()?;
+        self.senders.wake();
+        Some(msg)
+    }
+}
+
+/// A priority channel with room for `N` buffered messages.
+pub struct Priority<T, const N: usize> {
+    state: Mutex<State<T, N>>,
+}
+
+impl<T, const N: usize> Priority<T, N> {
+    /// Create a new, empty channel.
+    ///
+    /// Fails to compile if `N` is zero.
+    #[must_use]
+    pub const fn new() -> Self {
+        const { assert!(N > 0, "a `Priority` must have a non-zero capacity") };
+
+        Self {
+            state: Mutex::new(State {
+                slots: [const { None }; N],
+                next: 0,
+                senders: WakerSet::new(),
+                receivers: WakerSet::new(),
+            }),
+        }
+    }
+}
+
+impl<T, const N: usize> Default for Priority<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Ord, const N: usize> Priority<T, N> {
+    /// Try to send a message without waiting.
+    ///
+    /// # Errors
+    ///
+    /// Returns the message back if the channel is full.
+    pub fn try_send(&self, msg: T) -> Result<(), T> {
+        self.state.lock(|s| s.try_send(msg))
+    }
+
+    /// Send a message, waiting for room in the channel if necessary.
+    ///
+    /// Any number of tasks can wait to send at once. They sleep until a
+    /// message is received, rather than waking each other.
+    ///
+    /// ```rust
+    /// use core::task::Poll;
+    /// use woven::channel::priority::Priority;
+    /// use woven::test::{Step, WakeCounter};
+    ///
+    /// static A: WakeCounter = WakeCounter::new();
+    /// static B: WakeCounter = WakeCounter::new();
+    ///
+    /// let channel: Priority<u32, 1> = Priority::new();
+    /// channel.try_send(1).unwrap();
+    ///
+    /// let mut a = core::pin::pin!(Step::new(channel.send(2), &A));
+    /// let mut b = core::pin::pin!(Step::new(channel.send(3), &B));
+    /// assert_eq!(a.as_mut().poll(), Poll::Pending);
+    /// assert_eq!(b.as_mut().poll(), Poll::Pending);
+    ///
+    /// // Both senders are idle until there's room.
+    /// assert!(!a.woken() && !b.woken());
+    ///
+    /// assert_eq!(channel.try_recv(), Some(1));
+    /// assert!(a.woken() && b.woken());
+    /// ```
+    pub async fn send(&self, msg: T) {
+        let mut msg = Some(msg);
+
+        core::future::poll_fn(|cx| {
+            self.state.lock(|s| {
+                let Some(m) = msg.take() else { impossible!() };
+
+                match s.try_send(m) {
+                    Ok(()) => Poll::Ready(()),
+                    Err(m) => {
+                        msg = Some(m);
+                        s.senders.register(cx.waker());
+                        Poll::Pending
+                    }
+                }
+            })
+        })
+        .await;
+    }
+
+    /// Try to receive the highest priority message without waiting.
+    ///
+    /// Returns `None` if the channel is empty.
+    pub fn try_recv(&self) -> Option<T> {
+        self.state.lock(State::try_recv)
+    }
+
+    fn poll_recv(&self, cx: &mut Context<'_>) -> Poll<T> {
+        self.state.lock(|s| {
+            if let Some(msg) = s.try_recv() {
+                Poll::Ready(msg)
+            } else {
+                s.receivers.register(cx.waker());
+                Poll::Pending
+            }
+        })
+    }
+
+    /// Receive the highest priority message, waiting for one to be sent if
+    /// necessary.
+    pub async fn recv(&self) -> T {
+        core::future::poll_fn(|cx| self.poll_recv(cx)).await
+    }
+}
+
+/// Yields messages in priority order. Never ends.
+impl<T: Ord, const N: usize> Stream for &Priority<T, N> {
+    type Item = T;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.poll_recv(cx).map(Some)
+    }
+}
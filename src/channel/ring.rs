@@ -0,0 +1,74 @@
+/// A fixed capacity double ended queue backing the channels.
+pub(crate) struct Ring<T, const N: usize> {
+    buf: [Option<T>; N],
+    head: usize,
+    len: usize,
+}
+
+impl<T, const N: usize> Ring<T, N> {
+    pub(crate) const fn new() -> Self {
+        const { assert!(N > 0, "a ring buffer must have a non-zero capacity") };
+
+        Self {
+            buf: [const { None }; N],
+            head: 0,
+            len: 0,
+        }
+    }
+
+    // Only the lock-based channels need these.
+    #[cfg_attr(
+        not(any(
+            target_has_atomic = "ptr",
+            feature = "critical-section",
+            feature = "portable-atomic"
+        )),
+        allow(dead_code)
+    )]
+    pub(crate) fn len(&self) -> usize {
+        self.len
+    }
+
+    pub(crate) fn is_full(&self) -> bool {
+        self.len == N
+    }
+
+    pub(crate) fn push_back(&mut self, value: T) -> Result<(), T> {
+        if self.is_full() {
+            return Err(value);
+        }
+
+        let index = (self.head + self.len) % N;
+        self.buf[index] = Some(value);
+        self.len += 1;
+        Ok(())
+    }
+
+    pub(crate) fn pop_front(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+
+        let value = self.buf.get_mut(self.head)?.take();
+        self.head = (self.head + 1) % N;
+        self.len -= 1;
+        value
+    }
+
+    // Only the lock-based channels need these.
+    #[cfg_attr(
+        not(any(
+            target_has_atomic = "ptr",
+            feature = "critical-section",
+            feature = "portable-atomic"
+        )),
+        allow(dead_code)
+    )]
+    pub(crate) fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        if index >= self.len {
+            return None;
+        }
+
+        self.buf[(self.head + index) % N].as_mut()
+    }
+}
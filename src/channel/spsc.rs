@@ -0,0 +1,254 @@
+//! A single-producer single-consumer ring buffer.
+//!
+//! The buffer itself is lock-free: the ends coordinate through two atomic
+//! indices and never wait on each other, so the non-async methods are safe to
+//! call from interrupt handlers. This makes it suited to streaming data like
+//! UART bytes or ADC samples from an interrupt to a task.
+//!
+//! Registering and waking the other end's waker is lock-free too by default.
+//! With the `critical-section` feature it briefly takes a critical section
+//! instead, which is still safe from interrupt handlers.
+//!
+//! ```rust
+//! use woven::channel::spsc::Spsc;
+//!
+//! let mut ring: Spsc<u8, 4> = Spsc::new();
+//! let (mut producer, mut consumer) = ring.split();
+//!
+//! // E.g. from an interrupt handler.
+//! assert_eq!(producer.try_write(b"hello"), 4);
+//!
+//! cassette::block_on(async {
+//!     let mut buf = [0; 8];
+//!     let n = consumer.read(&mut buf).await;
+//!     assert_eq!(&buf[..n], b"hell");
+//! });
+//! ```
+
+use core::cell::UnsafeCell;
+use core::mem::MaybeUninit;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+
+use crate::stream::Stream;
+use crate::sync::atomic::{AtomicUsize, Ordering};
+use crate::sync::AtomicWaker;
+
+/// A single-producer single-consumer ring buffer with room for `N` elements.
+pub struct Spsc<T, const N: usize> {
+    buf: [UnsafeCell<MaybeUninit<T>>; N],
+    /// The read position, kept in `0..2 * N` so a full buffer can be told apart
+    /// from an empty one.
+    head: AtomicUsize,
+    /// The write position, kept in `0..2 * N`.
+    tail: AtomicUsize,
+    reader: AtomicWaker,
+    writer: AtomicWaker,
+}
+
+unsafe impl<T: Send, const N: usize> Sync for Spsc<T, N> {}
+
+impl<T: Copy, const N: usize> Spsc<T, N> {
+    /// Create a new, empty ring buffer.
+    #[must_use]
+    pub const fn new() -> Self {
+        const { assert!(N > 0, "an `Spsc` must have a non-zero capacity") };
+
+        Self {
+            buf: [const { UnsafeCell::new(MaybeUninit::uninit()) }; N],
+            head: AtomicUsize::new(0),
+            tail: AtomicUsize::new(0),
+            reader: AtomicWaker::new(),
+            writer: AtomicWaker::new(),
+        }
+    }
+
+    /// Split the ring buffer into its two ends.
+    pub fn split(&mut self) -> (Producer<'_, T, N>, Consumer<'_, T, N>) {
+        (Producer { ring: self }, Consumer { ring: self })
+    }
+
+    fn len(head: usize, tail: usize) -> usize {
+        (tail + 2 * N - head) % (2 * N)
+    }
+}
+
+impl<T: Copy, const N: usize> Default for Spsc<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The writing end of an [`Spsc`].
+pub struct Producer<'a, T, const N: usize> {
+    ring: &'a Spsc<T, N>,
+}
+
+impl<T: Copy, const N: usize> Producer<'_, T, N> {
+    /// Write as many elements from `data` as there is room for, returning how
+    /// many were written.
+    pub fn try_write(&mut self, data: &[T]) -> usize {
+        let ring = self.ring;
+        let head = ring.head.load(Ordering::Acquire);
+        let tail = ring.tail.load(Ordering::Relaxed);
+
+        let count = (N - Spsc::<T, N>::len(head, tail)).min(data.len());
+        for (i, value) in data[..count].iter().enumerate() {
+            // The consumer never reads past `tail`, so these slots are ours.
+            unsafe { (*ring.buf[(tail + i) % N].get()).write(*value) };
+        }
+
+        if count > 0 {
+            ring.tail.store((tail + count) % (2 * N), Ordering::Release);
+            ring.reader.wake();
+        }
+
+        count
+    }
+
+    /// Try to write a single element.
+    ///
+    /// # Errors
+    ///
+    /// Returns the element back if the buffer is full.
+    pub fn try_push(&mut self, value: T) -> Result<(), T> {
+        if self.try_write(core::slice::from_ref(&value)) == 1 {
+            Ok(())
+        } else {
+            Err(value)
+        }
+    }
+
+    /// Write as many elements from `data` as there is room for, waiting until
+    /// at least one can be written. Returns how many were written.
+    ///
+    /// Only returns `0` if `data` is empty.
+    pub async fn write(&mut self, data: &[T]) -> usize {
+        core::future::poll_fn(|cx| {
+            let count = self.try_write(data);
+            if count > 0 || data.is_empty() {
+                return Poll::Ready(count);
+            }
+
+            self.ring.writer.register(cx.waker());
+
+            match self.try_write(data) {
+                0 => Poll::Pending,
+                count => Poll::Ready(count),
+            }
+        })
+        .await
+    }
+
+    /// Whether the buffer is currently full.
+    #[must_use]
+    pub fn is_full(&self) -> bool {
+        let head = self.ring.head.load(Ordering::Acquire);
+        let tail = self.ring.tail.load(Ordering::Relaxed);
+        Spsc::<T, N>::len(head, tail) == N
+    }
+}
+
+/// The reading end of an [`Spsc`].
+pub struct Consumer<'a, T, const N: usize> {
+    ring: &'a Spsc<T, N>,
+}
+
+impl<T: Copy, const N: usize> Consumer<'_, T, N> {
+    /// Read as many elements into `buf` as are available, returning how many
+    /// were read.
+    pub fn try_read(&mut self, buf: &mut [T]) -> usize {
+        let ring = self.ring;
+        let head = ring.head.load(Ordering::Relaxed);
+        let tail = ring.tail.load(Ordering::Acquire);
+
+        let count = Spsc::<T, N>::len(head, tail).min(buf.len());
+        for (i, value) in buf[..count].iter_mut().enumerate() {
+            // The producer never writes past `head`, so these slots are ours
+            // and were initialised before `tail` was published.
+            *value = unsafe { (*ring.buf[(head + i) % N].get()).assume_init() };
+        }
+
+        if count > 0 {
+            ring.head.store((head + count) % (2 * N), Ordering::Release);
+            ring.writer.wake();
+        }
+
+        count
+    }
+
+    /// Try to read a single element.
+    ///
+    /// Returns `None` if the buffer is empty.
+    pub fn try_pop(&mut self) -> Option<T> {
+        let ring = self.ring;
+        let head = ring.head.load(Ordering::Relaxed);
+        let tail = ring.tail.load(Ordering::Acquire);
+
+        if Spsc::<T, N>::len(head, tail) == 0 {
+            return None;
+        }
+
+        let value = unsafe { (*ring.buf[head % N].get()).assume_init() };
+        ring.head.store((head + 1) % (2 * N), Ordering::Release);
+        ring.writer.wake();
+
+        Some(value)
+    }
+
+    fn poll_pop(&mut self, cx: &mut Context<'_>) -> Poll<T> {
+        if let Some(value) = self.try_pop() {
+            return Poll::Ready(value);
+        }
+
+        self.ring.reader.register(cx.waker());
+
+        match self.try_pop() {
+            Some(value) => Poll::Ready(value),
+            None => Poll::Pending,
+        }
+    }
+
+    /// Read a single element, waiting until one is available.
+    pub async fn pop(&mut self) -> T {
+        core::future::poll_fn(|cx| self.poll_pop(cx)).await
+    }
+
+    /// Read as many elements into `buf` as are available, waiting until at
+    /// least one can be read. Returns how many were read.
+    ///
+    /// Only returns `0` if `buf` is empty.
+    pub async fn read(&mut self, buf: &mut [T]) -> usize {
+        core::future::poll_fn(|cx| {
+            let count = self.try_read(buf);
+            if count > 0 || buf.is_empty() {
+                return Poll::Ready(count);
+            }
+
+            self.ring.reader.register(cx.waker());
+
+            match self.try_read(buf) {
+                0 => Poll::Pending,
+                count => Poll::Ready(count),
+            }
+        })
+        .await
+    }
+
+    /// Whether the buffer is currently empty.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        let head = self.ring.head.load(Ordering::Relaxed);
+        let tail = self.ring.tail.load(Ordering::Acquire);
+        Spsc::<T, N>::len(head, tail) == 0
+    }
+}
+
+/// Yields each element written by the producer. Never ends.
+impl<T: Copy, const N: usize> Stream for Consumer<'_, T, N> {
+    type Item = T;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.poll_pop(cx).map(Some)
+    }
+}
@@ -0,0 +1,178 @@
+//! A watch channel, holding only the most recently sent value.
+//!
+//! Receivers don't see every value sent, just the latest one. This makes it a
+//! good fit for propagating configuration or state, where only the current
+//! value matters.
+//!
+//! ```rust
+//! use woven::channel::watch::Watch;
+//!
+//! static MODE: Watch<u8, 2> = Watch::new(0);
+//!
+//! cassette::block_on(async {
+//!     let mut rx = MODE.receiver().unwrap();
+//!
+//!     MODE.send(1);
+//!     MODE.send(2);
+//!
+//!     rx.changed().await;
+//!     assert_eq!(rx.get(), 2);
+//!     assert!(!rx.has_changed());
+//! });
+//! ```
+
+use core::pin::Pin;
+use core::task::{Context, Poll};
+
+use crate::stream::Stream;
+use crate::sync::{Mutex, WakerSlot};
+
+struct State<T, const N: usize> {
+    value: T,
+    /// Incremented every time a new value is sent.
+    version: u64,
+    /// One entry per receiver slot, `None` when the slot is free.
+    receivers: [Option<WakerSlot>; N],
+}
+
+/// A watch channel with room for up to `N` simultaneous receivers.
+pub struct Watch<T, const N: usize> {
+    state: Mutex<State<T, N>>,
+}
+
+impl<T, const N: usize> Watch<T, N> {
+    /// Create a new channel holding the given initial value.
+    #[must_use]
+    pub const fn new(value: T) -> Self {
+        Self {
+            state: Mutex::new(State {
+                value,
+                version: 0,
+                receivers: [const { None }; N],
+            }),
+        }
+    }
+
+    /// Create a new receiver. The current value is considered already seen.
+    ///
+    /// Returns `None` if there are already `N` receivers.
+    pub fn receiver(&self) -> Option<Receiver<'_, T, N>> {
+        self.state.lock(|s| {
+            let id = s.receivers.iter().position(Option::is_none)?;
+            s.receivers[id] = Some(WakerSlot::new());
+
+            Some(Receiver {
+                channel: self,
+                id,
+                seen: s.version,
+            })
+        })
+    }
+
+    /// Replace the current value, notifying every receiver.
+    pub fn send(&self, value: T) {
+        self.send_modify(|current| *current = value);
+    }
+
+    /// Modify the current value in place, notifying every receiver.
+    ///
+    /// The channel is locked while `f` runs, so it must not use the channel.
+    ///
+    /// # Panics
+    ///
+    /// With the `critical-section` feature, panics if `f` uses the channel.
+    /// Otherwise that deadlocks.
+    pub fn send_modify(&self, f: impl FnOnce(&mut T)) {
+        self.state.lock(|s| {
+            f(&mut s.value);
+            s.version += 1;
+
+            for waker in s.receivers.iter_mut().flatten() {
+                waker.wake();
+            }
+        });
+    }
+
+    /// Get a copy of the current value.
+    #[must_use]
+    pub fn get(&self) -> T
+    where
+        T: Clone,
+    {
+        self.state.lock(|s| s.value.clone())
+    }
+}
+
+/// A receiver for a [`Watch`] channel.
+pub struct Receiver<'a, T, const N: usize> {
+    channel: &'a Watch<T, N>,
+    id: usize,
+    /// The version of the value this receiver last saw.
+    seen: u64,
+}
+
+impl<T, const N: usize> Receiver<'_, T, N> {
+    /// Whether a value has been sent since this receiver last saw one.
+    #[must_use]
+    pub fn has_changed(&self) -> bool {
+        self.channel.state.lock(|s| s.version != self.seen)
+    }
+
+    fn poll_changed<R>(&mut self, cx: &mut Context<'_>, f: impl FnOnce(&T) -> R) -> Poll<R> {
+        self.channel.state.lock(|s| {
+            if s.version == self.seen {
+                if let Some(waker) = &mut s.receivers[self.id] {
+                    waker.register(cx.waker());
+                }
+                Poll::Pending
+            } else {
+                self.seen = s.version;
+                Poll::Ready(f(&s.value))
+            }
+        })
+    }
+
+    /// Wait until a value has been sent since this receiver last saw one, and
+    /// mark it as seen.
+    pub async fn changed(&mut self) {
+        core::future::poll_fn(|cx| self.poll_changed(cx, |_| ())).await;
+    }
+
+    /// Access the current value.
+    ///
+    /// The channel is locked while `f` runs, so it should be kept short, and
+    /// must not use the channel.
+    ///
+    /// # Panics
+    ///
+    /// With the `critical-section` feature, panics if `f` uses the channel.
+    /// Otherwise that deadlocks.
+    pub fn borrow<R>(&self, f: impl FnOnce(&T) -> R) -> R {
+        self.channel.state.lock(|s| f(&s.value))
+    }
+
+    /// Get a copy of the current value.
+    #[must_use]
+    pub fn get(&self) -> T
+    where
+        T: Clone,
+    {
+        self.borrow(T::clone)
+    }
+}
+
+/// Yields a copy of each new value, skipping any that were replaced before the
+/// receiver could see them. Never ends.
+impl<T: Clone, const N: usize> Stream for Receiver<'_, T, N> {
+    type Item = T;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.poll_changed(cx, T::clone).map(Some)
+    }
+}
+
+impl<T, const N: usize> Drop for Receiver<'_, T, N> {
+    fn drop(&mut self) {
+        self.channel.state.lock(|s| s.receivers[self.id] = None);
+    }
+}
@@ -0,0 +1,91 @@
+//! Callbacks around every poll of a future.
+//!
+//! Wrapping a future in [`hooked`] calls a [`PollHook`] before and after each
+//! time it's polled, for custom profilers, stall detectors or kicking a
+//! watchdog. Wrap the branches of a combinator individually to observe each of
+//! them, since a hook takes `&self` the same one can be shared between them.
+//!
+//! ```rust
+//! use core::cell::Cell;
+//! use woven::hook::{hooked, PollHook};
+//! use woven::Join;
+//!
+//! #[derive(Default)]
+//! struct CountPolls(Cell<u32>);
+//!
+//! impl PollHook for CountPolls {
+//!     fn on_poll_start(&self) {
+//!         self.0.set(self.0.get() + 1);
+//!     }
+//! }
+//!
+//! let polls = CountPolls::default();
+//!
+//! cassette::block_on(async {
+//!     (hooked(async { 1 }, &polls), hooked(async { 2 }, &polls)).join().await;
+//! });
+//!
+//! assert_eq!(polls.0.get(), 2);
+//! ```
+
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+
+/// Callbacks run around every poll of a [`hooked`] future.
+pub trait PollHook {
+    /// Called just before the future is polled.
+    fn on_poll_start(&self) {}
+
+    /// Called just after the future is polled, with whether it completed.
+    fn on_poll_end(&self, ready: bool) {
+        let _ = ready;
+    }
+}
+
+impl<H: PollHook + ?Sized> PollHook for &H {
+    fn on_poll_start(&self) {
+        (**self).on_poll_start();
+    }
+
+    fn on_poll_end(&self, ready: bool) {
+        (**self).on_poll_end(ready);
+    }
+}
+
+/// Wrap a future, calling `hook` around every poll of it.
+pub fn hooked<F: Future, H: PollHook>(future: F, hook: H) -> Hooked<F, H> {
+    Hooked { future, hook }
+}
+
+/// The future returned by [`hooked`].
+pub struct Hooked<F, H> {
+    future: F,
+    hook: H,
+}
+
+impl<F, H> Hooked<F, H> {
+    /// Unwrap the hooked future.
+    pub fn into_inner(self) -> F {
+        self.future
+    }
+}
+
+impl<F: Future, H: PollHook> Future for Hooked<F, H> {
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = unsafe { self.get_unchecked_mut() };
+        this.hook.on_poll_start();
+        let poll = unsafe { Pin::new_unchecked(&mut this.future) }.poll(cx);
+        this.hook.on_poll_end(poll.is_ready());
+        poll
+    }
+}
+
+#[cfg(feature = "futures-core")]
+impl<F: futures_core::FusedFuture, H: PollHook> futures_core::FusedFuture for Hooked<F, H> {
+    fn is_terminated(&self) -> bool {
+        self.future.is_terminated()
+    }
+}
@@ -0,0 +1,19 @@
+use crate::Either;
+
+impl<A, B> From<either::Either<A, B>> for Either<A, B> {
+    fn from(either: either::Either<A, B>) -> Self {
+        match either {
+            either::Either::Left(a) => Self::First(a),
+            either::Either::Right(b) => Self::Second(b),
+        }
+    }
+}
+
+impl<A, B> From<Either<A, B>> for either::Either<A, B> {
+    fn from(either: Either<A, B>) -> Self {
+        match either {
+            Either::First(a) => Self::Left(a),
+            Either::Second(b) => Self::Right(b),
+        }
+    }
+}
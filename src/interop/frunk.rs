@@ -0,0 +1,66 @@
+//! The combinators for [`frunk_core`] heterogeneous lists.
+//!
+//! Joining an `HList` of futures produces an `HList` of their outputs, and
+//! racing one produces a `Coproduct` of their outputs.
+
+use core::future::Future;
+
+use frunk_core::coproduct::{CNil, Coproduct};
+use frunk_core::hlist::{HCons, HNil};
+
+use crate::sealed::Sealed;
+use crate::{Either, Join, Map, Race};
+
+impl Sealed for HNil {}
+
+impl<H, T> Sealed for HCons<H, T> {}
+
+impl Join for HNil {
+    type Output = HNil;
+
+    fn join(self) -> combinator_future!(Self::Output) {
+        Map::new(core::future::ready(()), |()| HNil)
+    }
+}
+
+impl<H, T> Join for HCons<H, T>
+where
+    H: Future,
+    T: Join,
+{
+    type Output = HCons<H::Output, T::Output>;
+
+    fn join(self) -> combinator_future!(Self::Output) {
+        Map::new((self.head, self.tail.join()).join(), |(head, tail)| HCons {
+            head,
+            tail,
+        })
+    }
+}
+
+/// An empty race never resolves.
+impl Race for HNil {
+    type Output = CNil;
+
+    fn race(self) -> combinator_future!(Self::Output) {
+        Map::new(core::future::pending(), |never| never)
+    }
+}
+
+impl<H, T> Race for HCons<H, T>
+where
+    H: Future,
+    T: Race,
+{
+    type Output = Coproduct<H::Output, T::Output>;
+
+    fn race(self) -> combinator_future!(Self::Output) {
+        Map::new(
+            (self.head, self.tail.race()).race(),
+            |either| match either {
+                Either::First(head) => Coproduct::Inl(head),
+                Either::Second(tail) => Coproduct::Inr(tail),
+            },
+        )
+    }
+}
@@ -0,0 +1,19 @@
+use crate::Either;
+
+impl<A, B> From<futures::future::Either<A, B>> for Either<A, B> {
+    fn from(either: futures::future::Either<A, B>) -> Self {
+        match either {
+            futures::future::Either::Left(a) => Self::First(a),
+            futures::future::Either::Right(b) => Self::Second(b),
+        }
+    }
+}
+
+impl<A, B> From<Either<A, B>> for futures::future::Either<A, B> {
+    fn from(either: Either<A, B>) -> Self {
+        match either {
+            Either::First(a) => Self::Left(a),
+            Either::Second(b) => Self::Right(b),
+        }
+    }
+}
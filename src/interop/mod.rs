@@ -0,0 +1,8 @@
+//! Conversions to and from other crates' types, each behind its own feature.
+
+#[cfg(feature = "either")]
+mod either;
+#[cfg(feature = "frunk")]
+mod frunk;
+#[cfg(feature = "futures")]
+mod futures;
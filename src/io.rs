@@ -0,0 +1,176 @@
+//! Extensions for [`embedded_io_async`] readers and writers.
+//!
+//! An `EitherN` of readers or writers is itself a reader or writer, so a
+//! transport can be chosen at runtime.
+//!
+//! ```rust
+//! use embedded_io_async::{Read, Write};
+//! use woven::channel::pipe::Pipe;
+//! use woven::Either;
+//!
+//! let uart: Pipe<8> = Pipe::new();
+//! let usb: Pipe<64> = Pipe::new();
+//!
+//! let use_usb = false;
+//!
+//! cassette::block_on(async {
+//!     let (mut writer, mut reader) = if use_usb {
+//!         (Either::First(usb.writer()), Either::First(usb.reader()))
+//!     } else {
+//!         (Either::Second(uart.writer()), Either::Second(uart.reader()))
+//!     };
+//!
+//!     writer.write_all(b"hi").await.unwrap();
+//!
+//!     let mut buf = [0; 2];
+//!     reader.read_exact(&mut buf).await.unwrap();
+//!     assert_eq!(&buf, b"hi");
+//! });
+//! ```
+
+use core::future::Future;
+use core::time::Duration;
+
+use embedded_io_async::{Error, ErrorKind, ErrorType, Read, Write};
+
+use crate::time::{timeout, Delay, Elapsed};
+
+/// The error returned by the timeout extension methods.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum TimeoutOrIoError<E> {
+    /// The operation didn't complete in time.
+    Timeout(Elapsed),
+    /// The operation failed.
+    Io(E),
+}
+
+impl<E> From<Elapsed> for TimeoutOrIoError<E> {
+    fn from(elapsed: Elapsed) -> Self {
+        Self::Timeout(elapsed)
+    }
+}
+
+impl<E: core::fmt::Display> core::fmt::Display for TimeoutOrIoError<E> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Timeout(elapsed) => elapsed.fmt(f),
+            Self::Io(e) => e.fmt(f),
+        }
+    }
+}
+
+impl<E: core::error::Error> core::error::Error for TimeoutOrIoError<E> {}
+
+impl<E: Error> Error for TimeoutOrIoError<E> {
+    fn kind(&self) -> ErrorKind {
+        match self {
+            Self::Timeout(_) => ErrorKind::TimedOut,
+            Self::Io(e) => e.kind(),
+        }
+    }
+}
+
+fn flatten<T, E>(result: Result<Result<T, E>, Elapsed>) -> Result<T, TimeoutOrIoError<E>> {
+    result?.map_err(TimeoutOrIoError::Io)
+}
+
+/// Timeout variants of the [`Read`] methods.
+///
+/// ```rust
+/// use core::time::Duration;
+/// use woven::channel::pipe::Pipe;
+/// use woven::io::{ReadTimeoutExt, TimeoutOrIoError};
+/// use woven::time::{Delay, Elapsed};
+///
+/// struct Expired;
+///
+/// impl Delay for Expired {
+///     async fn delay(&mut self, _: Duration) {}
+/// }
+///
+/// let pipe: Pipe<8> = Pipe::new();
+///
+/// cassette::block_on(async {
+///     let mut buf = [0; 8];
+///     let result = pipe.reader().read_timeout(&mut buf, &mut Expired, Duration::from_millis(10)).await;
+///     assert_eq!(result, Err(TimeoutOrIoError::Timeout(Elapsed)));
+/// });
+/// ```
+pub trait ReadTimeoutExt: Read {
+    /// [`Read::read`], giving up once `duration` has elapsed.
+    fn read_timeout(
+        &mut self,
+        buf: &mut [u8],
+        delay: &mut impl Delay,
+        duration: Duration,
+    ) -> impl Future<Output = Result<usize, TimeoutOrIoError<<Self as ErrorType>::Error>>>;
+}
+
+impl<T: Read> ReadTimeoutExt for T {
+    async fn read_timeout(
+        &mut self,
+        buf: &mut [u8],
+        delay: &mut impl Delay,
+        duration: Duration,
+    ) -> Result<usize, TimeoutOrIoError<T::Error>> {
+        flatten(timeout(delay, duration, self.read(buf)).await)
+    }
+}
+
+/// Timeout variants of the [`Write`] methods.
+pub trait WriteTimeoutExt: Write {
+    /// [`Write::write`], giving up once `duration` has elapsed.
+    fn write_timeout(
+        &mut self,
+        buf: &[u8],
+        delay: &mut impl Delay,
+        duration: Duration,
+    ) -> impl Future<Output = Result<usize, TimeoutOrIoError<<Self as ErrorType>::Error>>>;
+
+    /// [`Write::write_all`], giving up once `duration` has elapsed.
+    ///
+    /// If the timeout elapses, an unknown number of bytes may have been
+    /// written.
+    fn write_all_timeout(
+        &mut self,
+        buf: &[u8],
+        delay: &mut impl Delay,
+        duration: Duration,
+    ) -> impl Future<Output = Result<(), TimeoutOrIoError<<Self as ErrorType>::Error>>>;
+
+    /// [`Write::flush`], giving up once `duration` has elapsed.
+    fn flush_timeout(
+        &mut self,
+        delay: &mut impl Delay,
+        duration: Duration,
+    ) -> impl Future<Output = Result<(), TimeoutOrIoError<<Self as ErrorType>::Error>>>;
+}
+
+impl<T: Write> WriteTimeoutExt for T {
+    async fn write_timeout(
+        &mut self,
+        buf: &[u8],
+        delay: &mut impl Delay,
+        duration: Duration,
+    ) -> Result<usize, TimeoutOrIoError<T::Error>> {
+        flatten(timeout(delay, duration, self.write(buf)).await)
+    }
+
+    async fn write_all_timeout(
+        &mut self,
+        buf: &[u8],
+        delay: &mut impl Delay,
+        duration: Duration,
+    ) -> Result<(), TimeoutOrIoError<T::Error>> {
+        flatten(timeout(delay, duration, self.write_all(buf)).await)
+    }
+
+    async fn flush_timeout(
+        &mut self,
+        delay: &mut impl Delay,
+        duration: Duration,
+    ) -> Result<(), TimeoutOrIoError<T::Error>> {
+        flatten(timeout(delay, duration, self.flush()).await)
+    }
+}
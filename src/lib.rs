@@ -1,36 +1,662 @@
 #![doc = include_str!("../README.md")]
 #![no_std]
+#![cfg_attr(feature = "async-iterator", feature(async_iterator))]
+#![cfg_attr(feature = "nightly-variadic", feature(never_type))]
 
 use core::future::Future;
+use core::pin::Pin;
+
+/// The future returned by the combinators, which is also a `FusedFuture` when
+/// the `futures-core` feature is enabled. The traits using it are
+/// sealed, so this can't break implementations elsewhere.
+#[cfg(not(feature = "futures-core"))]
+macro_rules! combinator_future {
+    ($Output: ty) => { impl Future<Output = $Output> };
+}
+
+/// The future returned by the combinators, which is also a `FusedFuture` when
+/// the `futures-core` feature is enabled.
+#[cfg(feature = "futures-core")]
+macro_rules! combinator_future {
+    ($Output: ty) => { impl futures_core::FusedFuture<Output = $Output> };
+}
+
+/// Logs a combinator state transition at the debug level, through `log` and/or
+/// `defmt` when the `log` or `defmt-log` features are enabled.
+macro_rules! log_event {
+    ($( $arg: tt )*) => {
+        #[cfg(feature = "log")]
+        log::debug!($( $arg )*);
+        #[cfg(feature = "defmt-log")]
+        defmt::debug!($( $arg )*);
+    };
+}
+
+/// Evaluates to the result of polling one branch of a combinator, recording it
+/// as a `tracing` event when the `tracing` feature is enabled.
+#[cfg(not(feature = "tracing"))]
+macro_rules! trace_poll {
+    ($combinator: literal, $branch: expr, $poll: expr) => {
+        $poll
+    };
+}
+
+/// Evaluates to the result of polling one branch of a combinator, recording it
+/// as a `tracing` event when the `tracing` feature is enabled.
+#[cfg(feature = "tracing")]
+macro_rules! trace_poll {
+    ($combinator: literal, $branch: expr, $poll: expr) => {{
+        let poll = $poll;
+        tracing::trace!(
+            combinator = $combinator,
+            branch = $branch,
+            ready = crate::Readiness::is_ready(&poll),
+        );
+        poll
+    }};
+}
+
+/// Marks a path ruled out by the crate's own invariants, which is an
+/// optimisation hint rather than a panic when the `no-panic` feature is enabled.
+#[cfg(not(feature = "no-panic"))]
+macro_rules! impossible {
+    () => {
+        unreachable!()
+    };
+}
+
+/// Marks a path ruled out by the crate's own invariants, which is an
+/// optimisation hint rather than a panic when the `no-panic` feature is enabled.
+#[cfg(feature = "no-panic")]
+macro_rules! impossible {
+    () => {
+        unsafe { core::hint::unreachable_unchecked() }
+    };
+}
+
+/// Items that need the channels' internal lock, which is only available on
+/// targets with atomic compare-and-swap, or with the `critical-section` or
+/// `portable-atomic` feature.
+macro_rules! cfg_locking {
+    ($( $item: item )*) => {
+        $(
+            #[cfg(any(
+                target_has_atomic = "ptr",
+                feature = "critical-section",
+                feature = "portable-atomic"
+            ))]
+            $item
+        )*
+    };
+}
+
+cfg_locking! {
+    pub mod actor;
+    pub mod rpc;
+}
+
+pub mod channel;
+pub mod hook;
+#[cfg(feature = "embedded-io")]
+pub mod io;
+#[cfg(feature = "metrics")]
+pub mod metrics;
+pub mod prelude;
+pub mod stream;
+#[cfg(feature = "test-util")]
+pub mod test;
+pub mod time;
+#[cfg(feature = "nightly-variadic")]
+pub mod variadic;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+mod interop;
+mod sealed;
+mod select;
+mod sync;
+
+#[doc(hidden)]
+pub use select::__select_start;
 
 /// Combine multiple futures into one that resolves when all are done.
-pub trait Join {
+pub trait Join: sealed::Sealed {
+    /// The output type of the combined future.
+    type Output;
+
+    /// Combine multiple futures into one that resolves when all are done.
+    fn join(self) -> combinator_future!(Self::Output);
+}
+
+/// Combine multiple futures into one that resolves when all are done, reporting
+/// progress along the way.
+///
+/// ```rust
+/// use woven::JoinProgress;
+///
+/// cassette::block_on(async {
+///     let mut reports = Vec::new();
+///     let result = (async { 1 }, async { 2 })
+///         .join_progress(|completed| reports.push(completed))
+///         .await;
+///
+///     assert_eq!(result, (1, 2));
+///     assert_eq!(reports, [0b11]);
+/// });
+/// ```
+pub trait JoinProgress: sealed::Sealed {
     /// The output type of the combined future.
     type Output;
 
     /// Combine multiple futures into one that resolves when all are done.
-    fn join(self) -> impl Future<Output = Self::Output>;
+    ///
+    /// Whenever one or more of the futures complete, `progress` is called with
+    /// a mask of every future completed so far, where bit `n` is set once the
+    /// future at index `n` is done.
+    fn join_progress(self, progress: impl FnMut(u16)) -> combinator_future!(Self::Output);
+}
+
+/// Combine multiple futures into one that resolves when all are done, passing
+/// their outputs straight to a function.
+///
+/// ```rust
+/// use woven::JoinWith;
+///
+/// cassette::block_on(async {
+///     let sum = (async { 1 }, async { 2 }, async { 3 })
+///         .join_with(|a, b, c| a + b + c)
+///         .await;
+///     assert_eq!(sum, 6);
+/// });
+/// ```
+pub trait JoinWith<F>: sealed::Sealed {
+    /// The output type of the combined future.
+    type Output;
+
+    /// Combine multiple futures into one that resolves when all are done,
+    /// calling `f` with their outputs.
+    fn join_with(self, f: F) -> combinator_future!(Self::Output);
 }
 
 /// Combine multiple futures into one that resolves when any single one is done.
-pub trait Race {
+pub trait Race: sealed::Sealed {
     /// The output type of the combined future.
     type Output;
 
     /// Combine multiple futures into one that resolves when any single one is
     /// done.
-    fn race(self) -> impl Future<Output = Self::Output>;
+    fn race(self) -> combinator_future!(Self::Output);
+}
+
+/// Combine multiple futures into one that resolves when any single one is done,
+/// mapping whichever finishes first to a common output.
+///
+/// ```rust
+/// use woven::RaceMap;
+///
+/// cassette::block_on(async {
+///     let result = (core::future::pending::<u32>(), async { "two" })
+///         .race_map((|n: u32| n.to_string(), |s: &str| s.to_uppercase()))
+///         .await;
+///     assert_eq!(result, "TWO");
+/// });
+/// ```
+pub trait RaceMap<M>: sealed::Sealed {
+    /// The output type of the combined future.
+    type Output;
+
+    /// Combine multiple futures into one that resolves when any single one is
+    /// done, applying the function at the same position in `maps` to its
+    /// output.
+    fn race_map(self, maps: M) -> combinator_future!(Self::Output);
 }
 
 /// Combine multiple futures with the same output into one that resolves when
 /// any single one is done.
-pub trait RaceSame {
+pub trait RaceSame: sealed::Sealed {
     /// The output type of the combined future.
     type Output;
 
     /// Combine multiple futures with the same output into one that resolves
     /// when any single one is done.
-    fn race_same(self) -> impl Future<Output = Self::Output>;
+    fn race_same(self) -> combinator_future!(Self::Output);
+
+    /// Like [`race_same`](RaceSame::race_same), but also resolves to the
+    /// zero-based index of the future that finished first.
+    ///
+    /// ```rust
+    /// use woven::RaceSame;
+    ///
+    /// cassette::block_on(async {
+    ///     let result = (core::future::pending(), async { 2 }).race_same_indexed().await;
+    ///     assert_eq!(result, (1, 2));
+    /// });
+    /// ```
+    fn race_same_indexed(self) -> combinator_future!((usize, Self::Output));
+}
+
+/// Combine multiple futures into one that resolves when any are done, with the
+/// output of every future that was ready on that same poll.
+///
+/// Unlike [`Race`], which stops at the first ready future it finds, every
+/// future is polled, so simultaneous completions aren't lost.
+///
+/// ```rust
+/// use woven::RaceAllReady;
+///
+/// cassette::block_on(async {
+///     let result = (async { 1 }, core::future::pending::<u32>(), async { 3 })
+///         .race_all_ready()
+///         .await;
+///     assert_eq!(result, (Some(1), None, Some(3)));
+/// });
+/// ```
+pub trait RaceAllReady: sealed::Sealed {
+    /// The output type of the combined future, with an `Option` for each
+    /// future.
+    type Output;
+
+    /// Combine multiple futures into one that resolves when any are done, with
+    /// the output of every future that was ready on that poll.
+    fn race_all_ready(self) -> combinator_future!(Self::Output);
+}
+
+/// Turn multiple futures into a stream of their outputs, in the order they
+/// complete.
+///
+/// ```rust
+/// use woven::stream::StreamExt;
+/// use woven::{CompletionStream, Either};
+///
+/// cassette::block_on(async {
+///     let mut polled = false;
+///     let slow = core::future::poll_fn(move |cx| {
+///         if polled {
+///             return core::task::Poll::Ready("slow");
+///         }
+///         polled = true;
+///         cx.waker().wake_by_ref();
+///         core::task::Poll::Pending
+///     });
+///
+///     let mut results = core::pin::pin!((slow, async { 2 }).completion_stream());
+///     assert_eq!(results.next().await, Some(Either::Second(2)));
+///     assert_eq!(results.next().await, Some(Either::First("slow")));
+///     assert_eq!(results.next().await, None);
+/// });
+/// ```
+pub trait CompletionStream {
+    /// The type of each item in the stream.
+    type Item;
+
+    /// Turn multiple futures into a stream that yields each one's output as it
+    /// completes, ending once all are done.
+    fn completion_stream(self) -> impl stream::Stream<Item = Self::Item>;
+}
+
+/// Race multiple futures, handing back the ones that didn't finish so they can
+/// keep being driven.
+///
+/// The futures must be [`Unpin`], pin them first with [`core::pin::pin!`] if
+/// they aren't.
+///
+/// ```rust
+/// use core::pin::pin;
+/// use core::task::Poll;
+/// use woven::{Either, RaceKeepRest};
+///
+/// cassette::block_on(async {
+///     let mut polled = false;
+///     let slow = pin!(core::future::poll_fn(move |cx| {
+///         if polled {
+///             return Poll::Ready(2);
+///         }
+///         polled = true;
+///         cx.waker().wake_by_ref();
+///         Poll::Pending
+///     }));
+///     let fast = pin!(async { 1 });
+///
+///     let (winner, (slow, fast)) = (slow, fast).race_keep_rest().await;
+///     assert_eq!(winner, Either::Second(1));
+///     assert!(fast.is_none());
+///
+///     // The losing future can still be driven to completion.
+///     assert_eq!(slow.unwrap().await, 2);
+/// });
+/// ```
+pub trait RaceKeepRest: sealed::Sealed {
+    /// The output type of the combined future.
+    type Output;
+    /// The futures handed back, each `None` if it was the one that finished.
+    type Rest;
+
+    /// Race multiple futures, resolving to the winner's output alongside the
+    /// futures that didn't finish.
+    fn race_keep_rest(self) -> combinator_future!((Self::Output, Self::Rest));
+}
+
+/// Combine multiple futures with the same output into one that resolves when
+/// `K` of them are done. Implemented for tuples and arrays.
+///
+/// ```rust
+/// use woven::FirstN;
+///
+/// cassette::block_on(async {
+///     let replicas = [1, 2, 3].map(|n| async move {
+///         if n == 2 {
+///             core::future::pending::<()>().await;
+///         }
+///         n
+///     });
+///
+///     let results = replicas.first_n::<2>().await;
+///     assert_eq!(results, [(0, 1), (2, 3)]);
+///
+///     let results = (core::future::pending(), async { 2 }, async { 3 }).first_n::<1>().await;
+///     assert_eq!(results, [(1, 2)]);
+/// });
+/// ```
+pub trait FirstN: sealed::Sealed {
+    /// The output type of each future.
+    type Output;
+
+    /// Combine multiple futures with the same output into one that resolves
+    /// when `K` of them are done, with each output alongside the index of its
+    /// future, in the order they completed. The remaining futures are dropped.
+    ///
+    /// Fails to compile if `K` is greater than the number of futures.
+    fn first_n<const K: usize>(self) -> combinator_future!([(usize, Self::Output); K]);
+}
+
+/// Combine multiple fallible futures with the same output into one that resolves
+/// once `K` of them succeed, or as soon as that becomes impossible. Implemented
+/// for tuples and arrays.
+///
+/// ```rust
+/// use woven::Quorum;
+///
+/// cassette::block_on(async {
+///     let replicas = [Ok(1), Err("down"), Ok(3)].map(|r| async move { r });
+///     assert_eq!(replicas.quorum::<2>().await, Ok([(0, 1), (2, 3)]));
+///
+///     let replicas = [Err("down"), Err("timeout"), Ok(3)].map(|r| async move { r });
+///     assert_eq!(replicas.quorum::<2>().await, Err([Some("down"), Some("timeout"), None]));
+/// });
+/// ```
+pub trait Quorum: sealed::Sealed {
+    /// The success type of each future.
+    type Ok;
+    /// The errors reported when a quorum can't be reached, one slot per future.
+    type Errors;
+
+    /// Combine multiple fallible futures with the same output into one that
+    /// resolves once `K` of them succeed, with each success alongside the index
+    /// of its future, in the order they completed. The remaining futures are
+    /// dropped.
+    ///
+    /// Fails to compile if `K` is greater than the number of futures.
+    ///
+    /// # Errors
+    ///
+    /// Resolves early once enough futures have failed that `K` successes are no
+    /// longer possible, with the error from each future that failed.
+    fn quorum<const K: usize>(
+        self,
+    ) -> combinator_future!(Result<[(usize, Self::Ok); K], Self::Errors>);
+}
+
+/// Combine an array of futures into one that resolves when all are done, while
+/// running at most `limit` of them at a time.
+///
+/// ```rust
+/// use core::cell::Cell;
+/// use woven::JoinLimited;
+///
+/// cassette::block_on(async {
+///     let running = Cell::new(0);
+///     let most = Cell::new(0);
+///
+///     let writes = [1, 2, 3, 4, 5].map(|n| {
+///         let (running, most) = (&running, &most);
+///         async move {
+///             running.set(running.get() + 1);
+///             most.set(most.get().max(running.get()));
+///             let mut yielded = false;
+///             core::future::poll_fn(|cx| {
+///                 if yielded {
+///                     return core::task::Poll::Ready(());
+///                 }
+///                 yielded = true;
+///                 cx.waker().wake_by_ref();
+///                 core::task::Poll::Pending
+///             })
+///             .await;
+///             running.set(running.get() - 1);
+///             n * 10
+///         }
+///     });
+///
+///     assert_eq!(writes.join_limited(2).await, [10, 20, 30, 40, 50]);
+///     assert_eq!(most.get(), 2);
+/// });
+/// ```
+pub trait JoinLimited: sealed::Sealed {
+    /// The output type of the combined future.
+    type Output;
+
+    /// Combine an array of futures into one that resolves when all are done,
+    /// with their outputs in the same order. Only the first `limit` futures are
+    /// polled at first, and each time one completes the next is started.
+    ///
+    /// A `limit` of zero is treated as one.
+    fn join_limited(self, limit: usize) -> combinator_future!(Self::Output);
+}
+
+/// Poll multiple futures once each, without waiting, reporting which completed.
+///
+/// Pass the futures by [`Pin<&mut F>`](core::pin::Pin) to keep driving the
+/// ones that didn't complete.
+///
+/// ```rust
+/// use core::pin::pin;
+/// use woven::JoinNow;
+///
+/// let mut pending = pin!(core::future::pending::<u32>());
+///
+/// let result = (async { 1 }, pending.as_mut()).join_now();
+/// assert_eq!(result, (Some(1), None));
+/// ```
+pub trait JoinNow {
+    /// The output type, with an `Option` for each future.
+    type Output;
+
+    /// Poll every future once with a no-op waker, returning the outputs of
+    /// those that completed.
+    fn join_now(self) -> Self::Output;
+}
+
+/// Poll multiple futures once each, without waiting, returning the first that
+/// completed.
+///
+/// ```rust
+/// use woven::{Either, RaceNow};
+///
+/// let result = (core::future::pending::<u32>(), async { "two" }).race_now();
+/// assert_eq!(result, Some(Either::Second("two")));
+/// ```
+pub trait RaceNow {
+    /// The output type, `None` if no future completed.
+    type Output;
+
+    /// Poll the futures in order with a no-op waker, returning the output of the
+    /// first to complete.
+    fn race_now(self) -> Self::Output;
+}
+
+/// Call multiple async closures, combining their futures into one that resolves
+/// when all are done.
+///
+/// Each closure is paired with its argument, and is only called when the
+/// combined future is first polled. Closures taking several arguments can take
+/// them as a tuple, and those taking none can take `()`.
+///
+/// ```rust
+/// use woven::JoinFn;
+///
+/// async fn scale(x: u32) -> u32 {
+///     x * 10
+/// }
+///
+/// cassette::block_on(async {
+///     let result = (
+///         (scale, 1),
+///         (async |(a, b): (u32, u32)| a + b, (2, 3)),
+///         (async |()| 4, ()),
+///     )
+///         .join_fn()
+///         .await;
+///     assert_eq!(result, (10, 5, 4));
+/// });
+/// ```
+pub trait JoinFn: sealed::Sealed {
+    /// The output type of the combined future.
+    type Output;
+
+    /// Call multiple async closures with their arguments, combining their
+    /// futures into one that resolves when all are done.
+    fn join_fn(self) -> combinator_future!(Self::Output);
+}
+
+/// Call multiple async closures, combining their futures into one that resolves
+/// when any single one is done.
+///
+/// As with [`JoinFn`], each closure is paired with its argument, and is only
+/// called when the combined future is first polled.
+///
+/// ```rust
+/// use woven::{Either, RaceFn};
+///
+/// cassette::block_on(async {
+///     let result = (
+///         (async |x: u32| x + 1, 1),
+///         (async |s: &str| s.len(), "two"),
+///     )
+///         .race_fn()
+///         .await;
+///     assert_eq!(result, Either::First(2));
+/// });
+/// ```
+pub trait RaceFn: sealed::Sealed {
+    /// The output type of the combined future.
+    type Output;
+
+    /// Call multiple async closures with their arguments, combining their
+    /// futures into one that resolves when any single one is done.
+    fn race_fn(self) -> combinator_future!(Self::Output);
+}
+
+/// Chain async functions, feeding the output of one into the next.
+///
+/// ```rust
+/// use woven::PipeInto;
+///
+/// async fn parse(s: &str) -> u32 {
+///     s.parse().unwrap()
+/// }
+///
+/// cassette::block_on(async {
+///     let parse_and_double = parse.pipe(async |n| n * 2);
+///     assert_eq!(parse_and_double("21").await, 42);
+/// });
+/// ```
+///
+/// For longer chains, [`compose!`] pipes any number of async functions.
+pub trait PipeInto<A, B>: AsyncFn(A) -> B + Sized {
+    /// Make an async function that calls `self`, then passes its output to
+    /// `next`.
+    fn pipe<C>(self, next: impl AsyncFn(B) -> C) -> impl AsyncFn(A) -> C {
+        async move |a| next(self(a).await).await
+    }
+}
+
+impl<F: AsyncFn(A) -> B, A, B> PipeInto<A, B> for F {}
+
+/// Compose async functions left to right, `compose!(f, g, h)` being
+/// `f.pipe(g).pipe(h)`.
+///
+/// ```rust
+/// use woven::compose;
+///
+/// cassette::block_on(async {
+///     let stack = compose!(async |n: u32| n + 1, async |n| n * 2, async |n| n.to_string());
+///     assert_eq!(stack(1).await, "4");
+/// });
+/// ```
+#[macro_export]
+macro_rules! compose {
+    ($f: expr $(,)?) => { $f };
+    ($f: expr, $g: expr $(, $rest: expr )* $(,)?) => {
+        $crate::compose!($crate::PipeInto::pipe($f, $g) $(, $rest )*)
+    };
+}
+
+/// Behaviour common to every `EitherN` enum, so generic code can work with
+/// race results regardless of their arity.
+///
+/// ```rust
+/// use woven::{Choice, Either, Either3};
+///
+/// fn describe<C: Choice>(choice: &C) -> (usize, usize) {
+///     (choice.index(), C::ARITY)
+/// }
+///
+/// assert_eq!(describe(&Either::<u8, u8>::Second(1)), (1, 2));
+/// assert_eq!(describe(&Either3::<u8, u8, u8>::Third(1)), (2, 3));
+/// ```
+pub trait Choice {
+    /// The number of variants.
+    const ARITY: usize;
+
+    /// The zero-based index of whichever variant this is.
+    fn index(&self) -> usize;
+}
+
+/// An `EitherN` enum where every variant has the same type.
+pub trait UniformChoice: Choice {
+    /// The type held by every variant.
+    type Inner;
+
+    /// Take the contained value.
+    fn into_inner(self) -> Self::Inner;
+
+    /// Take the contained value alongside the index of its variant.
+    fn into_indexed(self) -> (usize, Self::Inner)
+    where
+        Self: Sized,
+    {
+        (self.index(), self.into_inner())
+    }
+}
+
+/// Whether a branch's poll result means it's done, for [`trace_poll`].
+#[cfg(feature = "tracing")]
+trait Readiness {
+    fn is_ready(&self) -> bool;
+}
+
+#[cfg(feature = "tracing")]
+impl Readiness for bool {
+    fn is_ready(&self) -> bool {
+        *self
+    }
+}
+
+#[cfg(feature = "tracing")]
+impl<T> Readiness for core::task::Poll<T> {
+    fn is_ready(&self) -> bool {
+        core::task::Poll::is_ready(self)
+    }
 }
 
 enum MaybeDone<Fut: Future> {
@@ -45,12 +671,23 @@ enum MaybeDone<Fut: Future> {
 impl<Fut: Future + Unpin> Unpin for MaybeDone<Fut> {}
 
 impl<Fut: Future> MaybeDone<Fut> {
-    fn poll(self: core::pin::Pin<&mut Self>, cx: &mut core::task::Context<'_>) -> bool {
+    /// Poll the future if it isn't done yet, returning whether it's done.
+    /// `branch` names it in logs.
+    #[cfg_attr(
+        not(any(feature = "log", feature = "defmt-log")),
+        allow(unused_variables)
+    )]
+    fn poll(
+        self: core::pin::Pin<&mut Self>,
+        cx: &mut core::task::Context<'_>,
+        branch: &'static str,
+    ) -> bool {
         let this = unsafe { self.get_unchecked_mut() };
 
         match this {
             Self::Future(fut) => match unsafe { core::pin::Pin::new_unchecked(fut) }.poll(cx) {
                 core::task::Poll::Ready(res) => {
+                    log_event!("join branch {} completed", branch);
                     *this = Self::Done(res);
                     true
                 }
@@ -60,146 +697,1855 @@ impl<Fut: Future> MaybeDone<Fut> {
         }
     }
 
-    fn take_output(&mut self) -> Fut::Output {
-        match &*self {
-            Self::Done(_) => {}
-            Self::Future(_) | Self::Gone => unreachable!(),
+    #[cfg(feature = "futures-core")]
+    fn is_gone(&self) -> bool {
+        matches!(self, Self::Gone)
+    }
+
+    /// Take the output, if the future is done and it hasn't been taken yet.
+    fn take_output(&mut self) -> Option<Fut::Output> {
+        match self {
+            Self::Done(_) => match core::mem::replace(self, Self::Gone) {
+                Self::Done(output) => Some(output),
+                _ => None,
+            },
+            Self::Future(_) | Self::Gone => None,
+        }
+    }
+}
+
+/// Applies a function to the output of a future.
+struct Map<Fut, F> {
+    future: Fut,
+    f: Option<F>,
+}
+
+impl<Fut, F> Map<Fut, F> {
+    fn new(future: Fut, f: F) -> Self {
+        Self { future, f: Some(f) }
+    }
+}
+
+impl<Fut, F, T> Future for Map<Fut, F>
+where
+    Fut: Future,
+    F: FnOnce(Fut::Output) -> T,
+{
+    type Output = T;
+
+    fn poll(
+        self: core::pin::Pin<&mut Self>,
+        cx: &mut core::task::Context<'_>,
+    ) -> core::task::Poll<Self::Output> {
+        let this = unsafe { self.get_unchecked_mut() };
+
+        // Already completed, don't poll the future again.
+        if this.f.is_none() {
+            return core::task::Poll::Pending;
         }
 
-        match core::mem::replace(self, Self::Gone) {
-            MaybeDone::Done(output) => output,
-            _ => unreachable!(),
+        match unsafe { core::pin::Pin::new_unchecked(&mut this.future) }.poll(cx) {
+            core::task::Poll::Ready(output) => match this.f.take() {
+                Some(f) => core::task::Poll::Ready(f(output)),
+                None => core::task::Poll::Pending,
+            },
+            core::task::Poll::Pending => core::task::Poll::Pending,
         }
     }
 }
 
-macro_rules! impl_combinators {
-    (
-        $Either: ident, $( $F: ident : $Nth: ident ),*
-    ) => {
-        impl< $( $F ),* > Join for ( $( $F ),* )
-        where
-            $( $F: Future ),*
-        {
-            type Output = ( $( $F::Output ),* );
+#[cfg(feature = "futures-core")]
+impl<Fut, F, T> futures_core::FusedFuture for Map<Fut, F>
+where
+    Fut: Future,
+    F: FnOnce(Fut::Output) -> T,
+{
+    fn is_terminated(&self) -> bool {
+        self.f.is_none()
+    }
+}
 
-            fn join(self) -> impl Future<Output = Self::Output> {
-                #[allow(non_snake_case)]
-                struct Join< $( $F: Future ),* > {
-                    $( $F: MaybeDone<$F> ),*
+/// The outputs collected so far by [`FirstN::first_n`].
+struct Collected<T, const K: usize> {
+    outputs: [Option<(usize, T)>; K],
+    len: usize,
+}
+
+impl<T, const K: usize> Collected<T, K> {
+    fn new() -> Self {
+        Self {
+            outputs: [const { None }; K],
+            len: 0,
+        }
+    }
+
+    fn is_full(&self) -> bool {
+        self.len == K
+    }
+
+    /// Add an output, unless `K` have already been collected.
+    fn push(&mut self, index: usize, output: T) {
+        if let Some(slot) = self.outputs.get_mut(self.len) {
+            *slot = Some((index, output));
+            self.len += 1;
+        }
+    }
+
+    fn take(&mut self) -> [(usize, T); K] {
+        core::array::from_fn(|i| match self.outputs[i].take() {
+            Some(output) => output,
+            None => impossible!(),
+        })
+    }
+}
+
+impl<F, const N: usize> sealed::Sealed for [F; N] {}
+
+impl<F: Future, const N: usize> FirstN for [F; N] {
+    type Output = F::Output;
+
+    fn first_n<const K: usize>(self) -> combinator_future!([(usize, F::Output); K]) {
+        struct FirstN<F: Future, const N: usize, const K: usize> {
+            futures: [Option<F>; N],
+            collected: Collected<F::Output, K>,
+            done: bool,
+        }
+
+        impl<F: Future, const N: usize, const K: usize> Future for FirstN<F, N, K> {
+            type Output = [(usize, F::Output); K];
+
+            fn poll(
+                self: core::pin::Pin<&mut Self>,
+                cx: &mut core::task::Context<'_>,
+            ) -> core::task::Poll<Self::Output> {
+                let this = unsafe { self.get_unchecked_mut() };
+                if this.done {
+                    return core::task::Poll::Pending;
                 }
 
-                impl< $( $F ),* > Future for Join< $( $F ),* >
-                where
-                    $( $F: Future ),*
-                {
-                    type Output = ( $( $F::Output ),* );
+                for (index, slot) in this.futures.iter_mut().enumerate() {
+                    if this.collected.is_full() {
+                        break;
+                    }
 
-                    fn poll(
-                        self: core::pin::Pin<&mut Self>,
-                        cx: &mut core::task::Context<'_>,
-                    ) -> core::task::Poll<Self::Output> {
-                        let this = unsafe { self.get_unchecked_mut() };
-                        let mut done = true;
-                        $(
-                            done &= unsafe { core::pin::Pin::new_unchecked(&mut this.$F) }.poll(cx);
-                        )*
-                        if done {
-                            core::task::Poll::Ready(($( this.$F.take_output(), )*))
-                        } else {
-                            core::task::Poll::Pending
+                    if let Some(future) = slot {
+                        if let core::task::Poll::Ready(x) = trace_poll!(
+                            "first_n",
+                            index,
+                            unsafe { core::pin::Pin::new_unchecked(future) }.poll(cx)
+                        ) {
+                            *slot = None;
+                            this.collected.push(index, x);
                         }
                     }
                 }
 
-                #[allow(non_snake_case)]
-                let ( $( $F ),* ) = self;
+                if this.collected.is_full() {
+                    log_event!("first_n resolved");
+                    this.done = true;
+                    core::task::Poll::Ready(this.collected.take())
+                } else {
+                    core::task::Poll::Pending
+                }
+            }
+        }
 
-                Join {
-                    $( $F: MaybeDone::Future( $F ) ),*
+        #[cfg(feature = "futures-core")]
+        impl<F: Future, const N: usize, const K: usize> futures_core::FusedFuture for FirstN<F, N, K> {
+            fn is_terminated(&self) -> bool {
+                self.done
+            }
+        }
+
+        const { assert!(K <= N, "can't wait for more futures than there are") };
+
+        FirstN {
+            futures: self.map(Some),
+            collected: Collected::new(),
+            done: false,
+        }
+    }
+}
+
+/// The results collected so far by [`Quorum::quorum`].
+struct Tally<T, E, const K: usize, const N: usize> {
+    successes: Collected<T, K>,
+    errors: [Option<E>; N],
+    failed: usize,
+}
+
+impl<T, E, const K: usize, const N: usize> Tally<T, E, K, N> {
+    fn new() -> Self {
+        Self {
+            successes: Collected::new(),
+            errors: [const { None }; N],
+            failed: 0,
+        }
+    }
+
+    /// Whether a quorum has either been reached or become impossible.
+    fn is_decided(&self) -> bool {
+        self.successes.is_full() || self.failed > N - K
+    }
+
+    fn record(&mut self, index: usize, result: Result<T, E>) {
+        match result {
+            Ok(output) => self.successes.push(index, output),
+            Err(e) => {
+                if let Some(slot) = self.errors.get_mut(index) {
+                    *slot = Some(e);
                 }
+                self.failed += 1;
             }
         }
+    }
 
-        /// An enum representing the output of a [`Race`] operation.
-        #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
-        pub enum $Either< $( $F ),* > {
-            $(
-                #[doc = concat!("The ", stringify!($Nth), " possible value.")]
-                $Nth( $F ),
-            )*
+    fn take(&mut self) -> Result<[(usize, T); K], [Option<E>; N]> {
+        if self.successes.is_full() {
+            log_event!("quorum reached");
+            Ok(self.successes.take())
+        } else {
+            log_event!("quorum failed");
+            Err(core::mem::replace(&mut self.errors, [const { None }; N]))
+        }
+    }
+}
+
+impl<T, E, F: Future<Output = Result<T, E>>, const N: usize> Quorum for [F; N] {
+    type Ok = T;
+    type Errors = [Option<E>; N];
+
+    fn quorum<const K: usize>(self) -> combinator_future!(Result<[(usize, T); K], [Option<E>; N]>) {
+        struct Quorum<T, E, F, const N: usize, const K: usize> {
+            futures: [Option<F>; N],
+            tally: Tally<T, E, K, N>,
+            done: bool,
         }
 
-        impl< $( $F ),* > Race for ( $( $F ),* )
+        impl<T, E, F, const N: usize, const K: usize> Future for Quorum<T, E, F, N, K>
         where
-            $( $F: Future ),*
+            F: Future<Output = Result<T, E>>,
         {
-            type Output = $Either< $( $F::Output ),* >;
+            type Output = Result<[(usize, T); K], [Option<E>; N]>;
 
-            async fn race(self) -> Self::Output {
-                #[allow(non_snake_case)]
-                let ( $( $F ),* ) = self;
+            fn poll(
+                self: core::pin::Pin<&mut Self>,
+                cx: &mut core::task::Context<'_>,
+            ) -> core::task::Poll<Self::Output> {
+                let this = unsafe { self.get_unchecked_mut() };
+                if this.done {
+                    return core::task::Poll::Pending;
+                }
 
-                $(
-                    #[allow(non_snake_case)]
-                    let mut $F = core::pin::pin!($F);
-                )*
+                for (index, slot) in this.futures.iter_mut().enumerate() {
+                    if this.tally.is_decided() {
+                        break;
+                    }
 
-                core::future::poll_fn(move |cx| {
-                    $(
-                        if let core::task::Poll::Ready(x) = $F.as_mut().poll(cx) {
-                            return core::task::Poll::Ready($Either::$Nth(x));
+                    if let Some(future) = slot {
+                        if let core::task::Poll::Ready(x) = trace_poll!(
+                            "quorum",
+                            index,
+                            unsafe { core::pin::Pin::new_unchecked(future) }.poll(cx)
+                        ) {
+                            *slot = None;
+                            this.tally.record(index, x);
                         }
-                    )*
+                    }
+                }
 
+                if this.tally.is_decided() {
+                    this.done = true;
+                    core::task::Poll::Ready(this.tally.take())
+                } else {
                     core::task::Poll::Pending
-                })
-                .await
+                }
             }
         }
 
-        impl<T, $( $F ),* > RaceSame for ( $( $F ),* )
+        #[cfg(feature = "futures-core")]
+        impl<T, E, F, const N: usize, const K: usize> futures_core::FusedFuture for Quorum<T, E, F, N, K>
         where
-            $( $F: Future<Output = T> ),*
+            F: Future<Output = Result<T, E>>,
         {
-            type Output = T;
+            fn is_terminated(&self) -> bool {
+                self.done
+            }
+        }
 
-            async fn race_same(self) -> Self::Output {
-                #[allow(non_snake_case)]
-                let ( $( $F ),* ) = self;
+        const { assert!(K <= N, "can't wait for more futures than there are") };
 
-                $(
-                    #[allow(non_snake_case)]
-                    let mut $F = core::pin::pin!($F);
-                )*
+        Quorum {
+            futures: self.map(Some),
+            tally: Tally::new(),
+            done: false,
+        }
+    }
+}
+
+impl<F: Future, const N: usize> JoinLimited for [F; N] {
+    type Output = [F::Output; N];
+
+    fn join_limited(self, limit: usize) -> combinator_future!(Self::Output) {
+        struct JoinLimited<F: Future, const N: usize> {
+            futures: [Option<F>; N],
+            outputs: [Option<F::Output>; N],
+            /// The number of futures that have been started.
+            started: usize,
+            limit: usize,
+            done: bool,
+        }
+
+        impl<F: Future, const N: usize> Future for JoinLimited<F, N> {
+            type Output = [F::Output; N];
 
-                core::future::poll_fn(move |cx| {
-                    $(
-                        if let core::task::Poll::Ready(x) = $F.as_mut().poll(cx) {
-                            return core::task::Poll::Ready(x);
+            fn poll(
+                self: core::pin::Pin<&mut Self>,
+                cx: &mut core::task::Context<'_>,
+            ) -> core::task::Poll<Self::Output> {
+                let this = unsafe { self.get_unchecked_mut() };
+                if this.done {
+                    return core::task::Poll::Pending;
+                }
+
+                let mut running = 0;
+                for (index, slot) in this.futures.iter_mut().enumerate() {
+                    if index == this.started {
+                        if running == this.limit {
+                            break;
+                        }
+                        this.started += 1;
+                    }
+
+                    if let Some(future) = slot {
+                        if let core::task::Poll::Ready(x) = trace_poll!(
+                            "join_limited",
+                            index,
+                            unsafe { core::pin::Pin::new_unchecked(future) }.poll(cx)
+                        ) {
+                            log_event!("join_limited branch {} completed", index);
+                            *slot = None;
+                            this.outputs[index] = Some(x);
+                        } else {
+                            running += 1;
                         }
-                    )*
+                    }
+                }
 
+                if this.outputs.iter().all(Option::is_some) {
+                    this.done = true;
+                    core::task::Poll::Ready(core::array::from_fn(|index| {
+                        let Some(output) = this.outputs[index].take() else {
+                            impossible!()
+                        };
+                        output
+                    }))
+                } else {
                     core::task::Poll::Pending
-                })
-                .await
+                }
             }
         }
-    };
-}
 
-impl_combinators!(Either, F0: First, F1: Second);
-impl_combinators!(Either3, F0: First, F1: Second, F2: Third);
-impl_combinators!(Either4, F0: First, F1: Second, F2: Third, F3: Fourth);
-impl_combinators!(Either5, F0: First, F1: Second, F2: Third, F3: Fourth, F4: Fifth);
-impl_combinators!(Either6, F0: First, F1: Second, F2: Third, F3: Fourth, F4: Fifth, F5: Sixth);
-impl_combinators!(Either7, F0: First, F1: Second, F2: Third, F3: Fourth, F4: Fifth, F5: Sixth, F6: Seventh);
-impl_combinators!(Either8, F0: First, F1: Second, F2: Third, F3: Fourth, F4: Fifth, F5: Sixth, F6: Seventh, F7: Eighth);
-impl_combinators!(Either9, F0: First, F1: Second, F2: Third, F3: Fourth, F4: Fifth, F5: Sixth, F6: Seventh, F7: Eighth, F8: Ninth);
-impl_combinators!(Either10, F0: First, F1: Second, F2: Third, F3: Fourth, F4: Fifth, F5: Sixth, F6: Seventh, F7: Eighth, F8: Ninth, F9: Tenth);
-impl_combinators!(Either11, F0: First, F1: Second, F2: Third, F3: Fourth, F4: Fifth, F5: Sixth, F6: Seventh, F7: Eighth, F8: Ninth, F9: Tenth, F10: Eleventh);
-impl_combinators!(Either12, F0: First, F1: Second, F2: Third, F3: Fourth, F4: Fifth, F5: Sixth, F6: Seventh, F7: Eighth, F8: Ninth, F9: Tenth, F10: Eleventh, F11: Twelfth);
-impl_combinators!(Either13, F0: First, F1: Second, F2: Third, F3: Fourth, F4: Fifth, F5: Sixth, F6: Seventh, F7: Eighth, F8: Ninth, F9: Tenth, F10: Eleventh, F11: Twelfth, F12: Thirteenth);
-impl_combinators!(Either14, F0: First, F1: Second, F2: Third, F3: Fourth, F4: Fifth, F5: Sixth, F6: Seventh, F7: Eighth, F8: Ninth, F9: Tenth, F10: Eleventh, F11: Twelfth, F12: Thirteenth, F13: Fourteenth);
-impl_combinators!(Either15, F0: First, F1: Second, F2: Third, F3: Fourth, F4: Fifth, F5: Sixth, F6: Seventh, F7: Eighth, F8: Ninth, F9: Tenth, F10: Eleventh, F11: Twelfth, F12: Thirteenth, F13: Fourteenth, F14: Fifteenth);
-impl_combinators!(Either16, F0: First, F1: Second, F2: Third, F3: Fourth, F4: Fifth, F5: Sixth, F6: Seventh, F7: Eighth, F8: Ninth, F9: Tenth, F10: Eleventh, F11: Twelfth, F12: Thirteenth, F13: Fourteenth, F14: Fifteenth, F15: Sixteenth);
+        #[cfg(feature = "futures-core")]
+        impl<F: Future, const N: usize> futures_core::FusedFuture for JoinLimited<F, N> {
+            fn is_terminated(&self) -> bool {
+                self.done
+            }
+        }
+
+        JoinLimited {
+            futures: self.map(Some),
+            outputs: [const { None }; N],
+            started: 0,
+            limit: limit.max(1),
+            done: false,
+        }
+    }
+}
+
+/// Attach a label to a future, so diagnostics can say which future they're
+/// about instead of just its position in a combinator.
+///
+/// The label is included in the `tracing`, `log` and `defmt-log` output, and in
+/// the error from [`Named::timeout`].
+///
+/// ```rust
+/// use woven::{named, Race};
+///
+/// cassette::block_on(async {
+///     let sensor = named("sensor", async { 20 });
+///     assert_eq!(sensor.label(), "sensor");
+///
+///     let result = (sensor, named("watchdog", core::future::pending::<u32>())).race().await;
+///     assert_eq!(result.into_inner(), 20);
+/// });
+/// ```
+pub fn named<F: Future>(label: &'static str, future: F) -> Named<F> {
+    Named { label, future }
+}
+
+/// The future returned by [`named`].
+pub struct Named<F> {
+    label: &'static str,
+    future: F,
+}
+
+impl<F> Named<F> {
+    /// The future's label.
+    pub fn label(&self) -> &'static str {
+        self.label
+    }
+
+    /// Unwrap the labelled future.
+    pub fn into_inner(self) -> F {
+        self.future
+    }
+}
+
+impl<F: Future> Named<F> {
+    /// [`time::timeout`] for this future, with the label in the error.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`NamedElapsed`](time::NamedElapsed) if the delay completes
+    /// before the future does.
+    pub async fn timeout(
+        self,
+        delay: &mut impl time::Delay,
+        duration: core::time::Duration,
+    ) -> Result<F::Output, time::NamedElapsed> {
+        let label = self.label;
+        time::timeout(delay, duration, self)
+            .await
+            .map_err(|_| time::NamedElapsed { label })
+    }
+}
+
+impl<F: Future> Future for Named<F> {
+    type Output = F::Output;
+
+    fn poll(
+        self: core::pin::Pin<&mut Self>,
+        cx: &mut core::task::Context<'_>,
+    ) -> core::task::Poll<Self::Output> {
+        let this = unsafe { self.get_unchecked_mut() };
+        let poll = unsafe { core::pin::Pin::new_unchecked(&mut this.future) }.poll(cx);
+
+        #[cfg(feature = "tracing")]
+        tracing::trace!(future = this.label, ready = poll.is_ready());
+
+        if poll.is_ready() {
+            log_event!("{} completed", this.label);
+        }
+
+        poll
+    }
+}
+
+#[cfg(feature = "futures-core")]
+impl<F: futures_core::FusedFuture> futures_core::FusedFuture for Named<F> {
+    fn is_terminated(&self) -> bool {
+        self.future.is_terminated()
+    }
+}
+
+/// Run `future` to completion while polling `driver` alongside it, resolving to
+/// `future`'s output.
+///
+/// For background work that has to keep running for anything else to make
+/// progress, like a network stack's driver. Unlike racing the two, the driver
+/// can never be mistaken for the result: if it does complete, `future` simply
+/// carries on alone.
+///
+/// ```rust
+/// use core::cell::Cell;
+///
+/// cassette::block_on(async {
+///     let ticks = Cell::new(0);
+///
+///     let driver = core::future::poll_fn(|cx| {
+///         ticks.set(ticks.get() + 1);
+///         cx.waker().wake_by_ref();
+///         core::task::Poll::<()>::Pending
+///     });
+///
+///     let app = core::future::poll_fn(|_| {
+///         if ticks.get() < 3 {
+///             core::task::Poll::Pending
+///         } else {
+///             core::task::Poll::Ready("connected")
+///         }
+///     });
+///
+///     assert_eq!(woven::with_driver(app, driver).await, "connected");
+/// });
+/// ```
+pub fn with_driver<F: Future, D: Future>(future: F, driver: D) -> WithDriver<F, D> {
+    WithDriver {
+        future,
+        driver,
+        driver_done: false,
+        done: false,
+    }
+}
+
+/// The future returned by [`with_driver`].
+pub struct WithDriver<F, D> {
+    future: F,
+    driver: D,
+    driver_done: bool,
+    done: bool,
+}
+
+impl<F: Future, D: Future> Future for WithDriver<F, D> {
+    type Output = F::Output;
+
+    fn poll(
+        self: core::pin::Pin<&mut Self>,
+        cx: &mut core::task::Context<'_>,
+    ) -> core::task::Poll<Self::Output> {
+        let this = unsafe { self.get_unchecked_mut() };
+        if this.done {
+            return core::task::Poll::Pending;
+        }
+
+        if !this.driver_done {
+            let driver = unsafe { core::pin::Pin::new_unchecked(&mut this.driver) };
+            if trace_poll!("with_driver", "driver", driver.poll(cx)).is_ready() {
+                log_event!("with_driver driver completed");
+                this.driver_done = true;
+            }
+        }
+
+        let future = unsafe { core::pin::Pin::new_unchecked(&mut this.future) };
+        let poll = trace_poll!("with_driver", "future", future.poll(cx));
+        this.done = poll.is_ready();
+        poll
+    }
+}
+
+#[cfg(feature = "futures-core")]
+impl<F: Future, D: Future> futures_core::FusedFuture for WithDriver<F, D> {
+    fn is_terminated(&self) -> bool {
+        self.done
+    }
+}
+
+/// Combine a slice of type-erased futures into one that resolves when all are
+/// done.
+///
+/// Unlike [`Join`], every call shares a single poll loop regardless of the
+/// futures' types, which keeps code size down for large fan-outs. The slice is
+/// reordered as futures complete.
+///
+/// ```rust
+/// use core::cell::Cell;
+/// use core::future::Future;
+/// use core::pin::{pin, Pin};
+///
+/// cassette::block_on(async {
+///     let count = Cell::new(0);
+///     let a = pin!(async { count.set(count.get() + 1) });
+///     let b = pin!(async { count.set(count.get() + 2) });
+///
+///     let mut futures: [Pin<&mut dyn Future<Output = ()>>; 2] = [a, b];
+///     woven::join_dyn(&mut futures).await;
+///     assert_eq!(count.get(), 3);
+/// });
+/// ```
+pub fn join_dyn<'a, 'b>(
+    futures: &'a mut [Pin<&'b mut dyn Future<Output = ()>>],
+) -> JoinDyn<'a, 'b> {
+    JoinDyn {
+        remaining: futures.len(),
+        futures,
+        done: false,
+    }
+}
+
+/// The future returned by [`join_dyn`].
+pub struct JoinDyn<'a, 'b> {
+    /// Completed futures are moved past `remaining`.
+    futures: &'a mut [Pin<&'b mut dyn Future<Output = ()>>],
+    remaining: usize,
+    done: bool,
+}
+
+impl Future for JoinDyn<'_, '_> {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut core::task::Context<'_>) -> core::task::Poll<()> {
+        let this = &mut *self;
+        if this.done {
+            return core::task::Poll::Pending;
+        }
+
+        let mut i = 0;
+
+        while i < this.remaining {
+            if trace_poll!("join_dyn", i, this.futures[i].as_mut().poll(cx)).is_ready() {
+                this.remaining -= 1;
+                this.futures.swap(i, this.remaining);
+            } else {
+                i += 1;
+            }
+        }
+
+        if this.remaining == 0 {
+            log_event!("join_dyn resolved");
+            this.done = true;
+            core::task::Poll::Ready(())
+        } else {
+            core::task::Poll::Pending
+        }
+    }
+}
+
+#[cfg(feature = "futures-core")]
+impl futures_core::FusedFuture for JoinDyn<'_, '_> {
+    fn is_terminated(&self) -> bool {
+        self.done
+    }
+}
+
+/// Like [`join_dyn`], but for fallible futures, resolving early with the first
+/// error.
+///
+/// # Errors
+///
+/// Returns the first error any of the futures resolve to. The others are left
+/// unfinished.
+///
+/// ```rust
+/// use core::future::Future;
+/// use core::pin::{pin, Pin};
+///
+/// cassette::block_on(async {
+///     let a = pin!(async { Ok(()) });
+///     let b = pin!(async { Err("failed") });
+///
+///     let mut futures: [Pin<&mut dyn Future<Output = Result<(), &str>>>; 2] = [a, b];
+///     assert_eq!(woven::try_join_dyn(&mut futures).await, Err("failed"));
+/// });
+/// ```
+pub fn try_join_dyn<'a, 'b, E>(
+    futures: &'a mut [Pin<&'b mut dyn Future<Output = Result<(), E>>>],
+) -> TryJoinDyn<'a, 'b, E> {
+    TryJoinDyn {
+        remaining: futures.len(),
+        futures,
+        done: false,
+    }
+}
+
+/// The future returned by [`try_join_dyn`].
+pub struct TryJoinDyn<'a, 'b, E> {
+    /// Completed futures are moved past `remaining`.
+    futures: &'a mut [Pin<&'b mut dyn Future<Output = Result<(), E>>>],
+    remaining: usize,
+    done: bool,
+}
+
+impl<E> Future for TryJoinDyn<'_, '_, E> {
+    type Output = Result<(), E>;
+
+    fn poll(
+        mut self: Pin<&mut Self>,
+        cx: &mut core::task::Context<'_>,
+    ) -> core::task::Poll<Self::Output> {
+        let this = &mut *self;
+        if this.done {
+            return core::task::Poll::Pending;
+        }
+
+        let mut i = 0;
+
+        while i < this.remaining {
+            match trace_poll!("try_join_dyn", i, this.futures[i].as_mut().poll(cx)) {
+                core::task::Poll::Ready(Ok(())) => {
+                    this.remaining -= 1;
+                    this.futures.swap(i, this.remaining);
+                }
+                core::task::Poll::Ready(Err(e)) => {
+                    log_event!("try_join_dyn failed");
+                    this.done = true;
+                    return core::task::Poll::Ready(Err(e));
+                }
+                core::task::Poll::Pending => i += 1,
+            }
+        }
+
+        if this.remaining == 0 {
+            log_event!("try_join_dyn resolved");
+            this.done = true;
+            core::task::Poll::Ready(Ok(()))
+        } else {
+            core::task::Poll::Pending
+        }
+    }
+}
+
+#[cfg(feature = "futures-core")]
+impl<E> futures_core::FusedFuture for TryJoinDyn<'_, '_, E> {
+    fn is_terminated(&self) -> bool {
+        self.done
+    }
+}
+
+/// Expands to the second argument, ignoring the first. Lets a repetition emit
+/// something once per element without using the element.
+macro_rules! replace {
+    ($_: tt, $with: tt) => {
+        $with
+    };
+}
+
+/// Generates a `map_*` method for each variant of an `EitherN`, working through
+/// the variants one at a time so the others' types can be carried over.
+macro_rules! impl_either_maps {
+    (
+        $Either: ident, [ $( $B: ident : $BNth: ident ),* ], []
+    ) => {};
+    (
+        $Either: ident,
+        [ $( $B: ident : $BNth: ident ),* ],
+        [ $F: ident : $Nth: ident : $map: ident $(, $A: ident : $ANth: ident : $amap: ident )* ]
+    ) => {
+        #[doc = concat!("Apply a function to the value if this is the `", stringify!($Nth), "` variant.")]
+        pub fn $map<U>(self, f: impl FnOnce($F) -> U) -> $Either< $( $B, )* U $(, $A )* > {
+            match self {
+                $( Self::$BNth(x) => $Either::$BNth(x), )*
+                Self::$Nth(x) => $Either::$Nth(f(x)),
+                $( Self::$ANth(x) => $Either::$ANth(x), )*
+            }
+        }
+
+        impl_either_maps!($Either, [ $( $B : $BNth, )* $F : $Nth ], [ $( $A : $ANth : $amap ),* ]);
+    };
+}
+
+// Each combinator polls its futures in its own loop. The futures' polls are
+// inlined there, so sharing a loop between combinators, or outlining it behind
+// `dyn Future`, makes firmware larger rather than smaller.
+macro_rules! impl_combinators {
+    (
+        $Either: ident, $( $F: ident : $A: ident : $Nth: ident : $map: ident ),*
+    ) => {
+        impl< $( $F ),* > sealed::Sealed for ( $( $F ),* ) {}
+
+        impl< $( $F ),* > Join for ( $( $F ),* )
+        where
+            $( $F: Future ),*
+        {
+            type Output = ( $( $F::Output ),* );
+
+            fn join(self) -> combinator_future!(Self::Output) {
+                #[allow(non_snake_case)]
+                struct Join< $( $F: Future ),* > {
+                    $( $F: MaybeDone<$F> ),*
+                }
+
+                impl< $( $F ),* > Future for Join< $( $F ),* >
+                where
+                    $( $F: Future ),*
+                {
+                    type Output = ( $( $F::Output ),* );
+
+                    fn poll(
+                        self: core::pin::Pin<&mut Self>,
+                        cx: &mut core::task::Context<'_>,
+                    ) -> core::task::Poll<Self::Output> {
+                        let this = unsafe { self.get_unchecked_mut() };
+                        let mut done = true;
+                        $(
+                            done &= trace_poll!("join", stringify!($Nth), unsafe { core::pin::Pin::new_unchecked(&mut this.$F) }.poll(cx, stringify!($Nth)));
+                        )*
+                        if done {
+                            #[allow(non_snake_case)]
+                            if let ( $( Some($F), )* ) = ( $( this.$F.take_output(), )* ) {
+                                return core::task::Poll::Ready(( $( $F ),* ));
+                            }
+                        }
+
+                        core::task::Poll::Pending
+                    }
+                }
+
+                #[cfg(feature = "futures-core")]
+                impl< $( $F ),* > futures_core::FusedFuture for Join< $( $F ),* >
+                where
+                    $( $F: Future ),*
+                {
+                    fn is_terminated(&self) -> bool {
+                        $( self.$F.is_gone() )&&*
+                    }
+                }
+
+                #[allow(non_snake_case)]
+                let ( $( $F ),* ) = self;
+
+                Join {
+                    $( $F: MaybeDone::Future( $F ) ),*
+                }
+            }
+        }
+
+        impl< $( $F ),* > JoinProgress for ( $( $F ),* )
+        where
+            $( $F: Future ),*
+        {
+            type Output = ( $( $F::Output ),* );
+
+            fn join_progress(self, progress: impl FnMut(u16)) -> combinator_future!(Self::Output) {
+                #[allow(non_snake_case)]
+                struct JoinProgress<P, $( $F: Future ),* > {
+                    $( $F: MaybeDone<$F>, )*
+                    progress: P,
+                    reported: u16,
+                }
+
+                impl<P, $( $F ),* > Future for JoinProgress<P, $( $F ),* >
+                where
+                    P: FnMut(u16),
+                    $( $F: Future ),*
+                {
+                    type Output = ( $( $F::Output ),* );
+
+                    #[allow(unused_assignments)]
+                    fn poll(
+                        self: core::pin::Pin<&mut Self>,
+                        cx: &mut core::task::Context<'_>,
+                    ) -> core::task::Poll<Self::Output> {
+                        let this = unsafe { self.get_unchecked_mut() };
+                        let mut done = true;
+                        let mut completed = 0;
+                        let mut bit = 1;
+                        $(
+                            if trace_poll!("join_progress", stringify!($Nth), unsafe { core::pin::Pin::new_unchecked(&mut this.$F) }.poll(cx, stringify!($Nth))) {
+                                completed |= bit;
+                            } else {
+                                done = false;
+                            }
+                            bit <<= 1;
+                        )*
+
+                        if completed != this.reported {
+                            this.reported = completed;
+                            (this.progress)(completed);
+                        }
+
+                        if done {
+                            #[allow(non_snake_case)]
+                            if let ( $( Some($F), )* ) = ( $( this.$F.take_output(), )* ) {
+                                return core::task::Poll::Ready(( $( $F ),* ));
+                            }
+                        }
+
+                        core::task::Poll::Pending
+                    }
+                }
+
+                #[cfg(feature = "futures-core")]
+                impl<P, $( $F ),* > futures_core::FusedFuture for JoinProgress<P, $( $F ),* >
+                where
+                    P: FnMut(u16),
+                    $( $F: Future ),*
+                {
+                    fn is_terminated(&self) -> bool {
+                        $( self.$F.is_gone() )&&*
+                    }
+                }
+
+                #[allow(non_snake_case)]
+                let ( $( $F ),* ) = self;
+
+                JoinProgress {
+                    $( $F: MaybeDone::Future( $F ), )*
+                    progress,
+                    reported: 0,
+                }
+            }
+        }
+
+        impl<U, Func, $( $F ),* > JoinWith<Func> for ( $( $F ),* )
+        where
+            Func: FnOnce( $( $F::Output ),* ) -> U,
+            $( $F: Future ),*
+        {
+            type Output = U;
+
+            #[allow(non_snake_case)]
+            fn join_with(self, f: Func) -> combinator_future!(U) {
+                Map::new(self.join(), move |( $( $F ),* )| f( $( $F ),* ))
+            }
+        }
+
+        /// An enum representing the output of a [`Race`] operation.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        #[cfg_attr(feature = "defmt", derive(defmt::Format))]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+        pub enum $Either< $( $F ),* > {
+            $(
+                #[doc = concat!("The ", stringify!($Nth), " possible value.")]
+                $Nth( $F ),
+            )*
+        }
+
+        impl< $( $F ),* > $Either< $( $F ),* > {
+            impl_either_maps!($Either, [], [ $( $F : $Nth : $map ),* ]);
+
+            /// Collapse into a single value, applying the function matching
+            /// whichever variant this is.
+            // The variant names double as the functions' types here.
+            #[allow(non_snake_case, clippy::too_many_arguments)]
+            pub fn fold<U, $( $Nth ),* >(self, $( $F: $Nth ),* ) -> U
+            where
+                $( $Nth: FnOnce($F) -> U ),*
+            {
+                match self {
+                    $( Self::$Nth(x) => $F(x), )*
+                }
+            }
+
+            /// The zero-based index of whichever variant this is.
+            #[allow(unused_assignments)]
+            pub fn index(&self) -> usize {
+                let mut index = 0;
+                $(
+                    if let Self::$Nth(_) = self {
+                        return index;
+                    }
+                    index += 1;
+                )*
+                // Every variant is checked above.
+                index
+            }
+
+            /// Borrow the contained value.
+            pub fn as_ref(&self) -> $Either< $( &$F ),* > {
+                match self {
+                    $( Self::$Nth(x) => $Either::$Nth(x), )*
+                }
+            }
+
+            /// Mutably borrow the contained value.
+            pub fn as_mut(&mut self) -> $Either< $( &mut $F ),* > {
+                match self {
+                    $( Self::$Nth(x) => $Either::$Nth(x), )*
+                }
+            }
+        }
+
+        impl<T> $Either< $( replace!($F, T) ),* > {
+            /// Take the contained value, when every variant has the same type.
+            pub fn into_inner(self) -> T {
+                match self {
+                    $( Self::$Nth(x) => x, )*
+                }
+            }
+
+            /// Take the contained value alongside the index of its variant,
+            /// when every variant has the same type.
+            pub fn into_indexed(self) -> (usize, T) {
+                (self.index(), self.into_inner())
+            }
+        }
+
+        impl< $( $F ),* > Choice for $Either< $( $F ),* > {
+            const ARITY: usize = [ $( replace!($F, ()) ),* ].len();
+
+            fn index(&self) -> usize {
+                $Either::index(self)
+            }
+        }
+
+        impl<T> UniformChoice for $Either< $( replace!($F, T) ),* > {
+            type Inner = T;
+
+            fn into_inner(self) -> T {
+                $Either::into_inner(self)
+            }
+        }
+
+        impl<T, $( $F ),* > Future for $Either< $( $F ),* >
+        where
+            $( $F: Future<Output = T> ),*
+        {
+            type Output = T;
+
+            fn poll(
+                self: core::pin::Pin<&mut Self>,
+                cx: &mut core::task::Context<'_>,
+            ) -> core::task::Poll<T> {
+                match unsafe { self.get_unchecked_mut() } {
+                    $( Self::$Nth(x) => unsafe { core::pin::Pin::new_unchecked(x) }.poll(cx), )*
+                }
+            }
+        }
+
+        #[cfg(feature = "futures-core")]
+        impl<T, $( $F ),* > futures_core::FusedFuture for $Either< $( $F ),* >
+        where
+            $( $F: futures_core::FusedFuture<Output = T> ),*
+        {
+            fn is_terminated(&self) -> bool {
+                match self {
+                    $( Self::$Nth(x) => x.is_terminated(), )*
+                }
+            }
+        }
+
+        impl<T, $( $F ),* > Iterator for $Either< $( $F ),* >
+        where
+            $( $F: Iterator<Item = T> ),*
+        {
+            type Item = T;
+
+            fn next(&mut self) -> Option<T> {
+                match self {
+                    $( Self::$Nth(x) => x.next(), )*
+                }
+            }
+
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                match self {
+                    $( Self::$Nth(x) => x.size_hint(), )*
+                }
+            }
+        }
+
+        impl<T, $( $F ),* > DoubleEndedIterator for $Either< $( $F ),* >
+        where
+            $( $F: DoubleEndedIterator<Item = T> ),*
+        {
+            fn next_back(&mut self) -> Option<T> {
+                match self {
+                    $( Self::$Nth(x) => x.next_back(), )*
+                }
+            }
+        }
+
+        impl<T, $( $F ),* > ExactSizeIterator for $Either< $( $F ),* >
+        where
+            $( $F: ExactSizeIterator<Item = T> ),*
+        {
+        }
+
+        impl<T, $( $F ),* > core::iter::FusedIterator for $Either< $( $F ),* >
+        where
+            $( $F: core::iter::FusedIterator<Item = T> ),*
+        {
+        }
+
+        impl<T, $( $F ),* > stream::Stream for $Either< $( $F ),* >
+        where
+            $( $F: stream::Stream<Item = T> ),*
+        {
+            type Item = T;
+
+            fn poll_next(
+                self: core::pin::Pin<&mut Self>,
+                cx: &mut core::task::Context<'_>,
+            ) -> core::task::Poll<Option<T>> {
+                match unsafe { self.get_unchecked_mut() } {
+                    $( Self::$Nth(x) => unsafe { core::pin::Pin::new_unchecked(x) }.poll_next(cx), )*
+                }
+            }
+        }
+
+        #[cfg(feature = "futures-core")]
+        impl<T, $( $F ),* > futures_core::Stream for $Either< $( $F ),* >
+        where
+            $( $F: futures_core::Stream<Item = T> ),*
+        {
+            type Item = T;
+
+            fn poll_next(
+                self: core::pin::Pin<&mut Self>,
+                cx: &mut core::task::Context<'_>,
+            ) -> core::task::Poll<Option<T>> {
+                match unsafe { self.get_unchecked_mut() } {
+                    $( Self::$Nth(x) => unsafe { core::pin::Pin::new_unchecked(x) }.poll_next(cx), )*
+                }
+            }
+
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                match self {
+                    $( Self::$Nth(x) => x.size_hint(), )*
+                }
+            }
+        }
+
+        impl< $( $F: core::fmt::Display ),* > core::fmt::Display for $Either< $( $F ),* > {
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                match self {
+                    $( Self::$Nth(x) => x.fmt(f), )*
+                }
+            }
+        }
+
+        impl< $( $F: core::error::Error ),* > core::error::Error for $Either< $( $F ),* > {
+            fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+                match self {
+                    $( Self::$Nth(x) => x.source(), )*
+                }
+            }
+        }
+
+        #[cfg(feature = "embedded-io")]
+        impl< $( $F: embedded_io_async::Error ),* > embedded_io_async::Error for $Either< $( $F ),* > {
+            fn kind(&self) -> embedded_io_async::ErrorKind {
+                match self {
+                    $( Self::$Nth(x) => x.kind(), )*
+                }
+            }
+        }
+
+        /// Errors from whichever variant this is are wrapped in the matching
+        /// variant.
+        #[cfg(feature = "embedded-io")]
+        impl< $( $F: embedded_io_async::ErrorType ),* > embedded_io_async::ErrorType for $Either< $( $F ),* > {
+            type Error = $Either< $( $F::Error ),* >;
+        }
+
+        #[cfg(feature = "embedded-io")]
+        impl< $( $F: embedded_io_async::Read ),* > embedded_io_async::Read for $Either< $( $F ),* > {
+            async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+                match self {
+                    $( Self::$Nth(x) => x.read(buf).await.map_err($Either::$Nth), )*
+                }
+            }
+        }
+
+        #[cfg(feature = "embedded-io")]
+        impl< $( $F: embedded_io_async::Write ),* > embedded_io_async::Write for $Either< $( $F ),* > {
+            async fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+                match self {
+                    $( Self::$Nth(x) => x.write(buf).await.map_err($Either::$Nth), )*
+                }
+            }
+
+            async fn flush(&mut self) -> Result<(), Self::Error> {
+                match self {
+                    $( Self::$Nth(x) => x.flush().await.map_err($Either::$Nth), )*
+                }
+            }
+        }
+
+        impl< $( $F ),* > Race for ( $( $F ),* )
+        where
+            $( $F: Future ),*
+        {
+            type Output = $Either< $( $F::Output ),* >;
+
+            fn race(self) -> combinator_future!(Self::Output) {
+                #[allow(non_snake_case)]
+                struct Race< $( $F ),* > {
+                    $( $F: $F, )*
+                    done: bool,
+                }
+
+                impl< $( $F ),* > Future for Race< $( $F ),* >
+                where
+                    $( $F: Future ),*
+                {
+                    type Output = $Either< $( $F::Output ),* >;
+
+                    fn poll(
+                        self: core::pin::Pin<&mut Self>,
+                        cx: &mut core::task::Context<'_>,
+                    ) -> core::task::Poll<Self::Output> {
+                        let this = unsafe { self.get_unchecked_mut() };
+                        if this.done {
+                            return core::task::Poll::Pending;
+                        }
+
+                        $(
+                            if let core::task::Poll::Ready(x) = trace_poll!("race", stringify!($Nth), unsafe { core::pin::Pin::new_unchecked(&mut this.$F) }.poll(cx)) {
+                                log_event!("race resolved by branch {}", stringify!($Nth));
+                                this.done = true;
+                                return core::task::Poll::Ready($Either::$Nth(x));
+                            }
+                        )*
+
+                        core::task::Poll::Pending
+                    }
+                }
+
+                #[cfg(feature = "futures-core")]
+                impl< $( $F ),* > futures_core::FusedFuture for Race< $( $F ),* >
+                where
+                    $( $F: Future ),*
+                {
+                    fn is_terminated(&self) -> bool {
+                        self.done
+                    }
+                }
+
+                #[allow(non_snake_case)]
+                let ( $( $F ),* ) = self;
+
+                Race {
+                    $( $F, )*
+                    done: false,
+                }
+            }
+        }
+
+        // The variant names double as the functions' types here.
+        impl<U, $( $F, $Nth ),* > RaceMap<( $( $Nth ),* )> for ( $( $F ),* )
+        where
+            $( $F: Future, $Nth: FnOnce($F::Output) -> U ),*
+        {
+            type Output = U;
+
+            #[allow(non_snake_case)]
+            fn race_map(self, maps: ( $( $Nth ),* )) -> combinator_future!(U) {
+                Map::new(self.race(), move |output: $Either< $( $F::Output ),* >| {
+                    let ( $( $F ),* ) = maps;
+                    output.fold( $( $F ),* )
+                })
+            }
+        }
+
+        impl< $( $F ),* > RaceAllReady for ( $( $F ),* )
+        where
+            $( $F: Future ),*
+        {
+            type Output = ( $( Option<$F::Output> ),* );
+
+            fn race_all_ready(self) -> combinator_future!(Self::Output) {
+                #[allow(non_snake_case)]
+                struct RaceAllReady< $( $F ),* > {
+                    $( $F: $F, )*
+                    done: bool,
+                }
+
+                impl< $( $F ),* > Future for RaceAllReady< $( $F ),* >
+                where
+                    $( $F: Future ),*
+                {
+                    type Output = ( $( Option<$F::Output> ),* );
+
+                    fn poll(
+                        self: core::pin::Pin<&mut Self>,
+                        cx: &mut core::task::Context<'_>,
+                    ) -> core::task::Poll<Self::Output> {
+                        let this = unsafe { self.get_unchecked_mut() };
+                        if this.done {
+                            return core::task::Poll::Pending;
+                        }
+
+                        #[allow(non_snake_case)]
+                        let output = ( $(
+                            match trace_poll!("race_all_ready", stringify!($Nth), unsafe { core::pin::Pin::new_unchecked(&mut this.$F) }.poll(cx)) {
+                                core::task::Poll::Ready(x) => {
+                                    log_event!("race_all_ready branch {} ready", stringify!($Nth));
+                                    Some(x)
+                                }
+                                core::task::Poll::Pending => None,
+                            }
+                        ),* );
+
+                        #[allow(non_snake_case)]
+                        let ( $( $F ),* ) = &output;
+                        if $( $F.is_some() )||* {
+                            this.done = true;
+                            core::task::Poll::Ready(output)
+                        } else {
+                            core::task::Poll::Pending
+                        }
+                    }
+                }
+
+                #[cfg(feature = "futures-core")]
+                impl< $( $F ),* > futures_core::FusedFuture for RaceAllReady< $( $F ),* >
+                where
+                    $( $F: Future ),*
+                {
+                    fn is_terminated(&self) -> bool {
+                        self.done
+                    }
+                }
+
+                #[allow(non_snake_case)]
+                let ( $( $F ),* ) = self;
+
+                RaceAllReady {
+                    $( $F, )*
+                    done: false,
+                }
+            }
+        }
+
+        impl< $( $F ),* > CompletionStream for ( $( $F ),* )
+        where
+            $( $F: Future ),*
+        {
+            type Item = $Either< $( $F::Output ),* >;
+
+            fn completion_stream(self) -> impl stream::Stream<Item = Self::Item> {
+                #[allow(non_snake_case)]
+                struct CompletionStream< $( $F ),* > {
+                    $( $F: Option<$F> ),*
+                }
+
+                impl< $( $F ),* > stream::Stream for CompletionStream< $( $F ),* >
+                where
+                    $( $F: Future ),*
+                {
+                    type Item = $Either< $( $F::Output ),* >;
+
+                    fn poll_next(
+                        self: core::pin::Pin<&mut Self>,
+                        cx: &mut core::task::Context<'_>,
+                    ) -> core::task::Poll<Option<Self::Item>> {
+                        let this = unsafe { self.get_unchecked_mut() };
+                        $(
+                            if let Some(future) = &mut this.$F {
+                                if let core::task::Poll::Ready(x) = trace_poll!("completion_stream", stringify!($Nth), unsafe { core::pin::Pin::new_unchecked(future) }.poll(cx)) {
+                                    log_event!("completion_stream branch {} completed", stringify!($Nth));
+                                    this.$F = None;
+                                    return core::task::Poll::Ready(Some($Either::$Nth(x)));
+                                }
+                            }
+                        )*
+
+                        if $( this.$F.is_none() )&&* {
+                            core::task::Poll::Ready(None)
+                        } else {
+                            core::task::Poll::Pending
+                        }
+                    }
+                }
+
+                #[allow(non_snake_case)]
+                let ( $( $F ),* ) = self;
+
+                CompletionStream {
+                    $( $F: Some($F) ),*
+                }
+            }
+        }
+
+        impl< $( $F ),* > RaceKeepRest for ( $( $F ),* )
+        where
+            $( $F: Future + Unpin ),*
+        {
+            type Output = $Either< $( $F::Output ),* >;
+            type Rest = ( $( Option<$F> ),* );
+
+            fn race_keep_rest(self) -> combinator_future!((Self::Output, Self::Rest)) {
+                #[allow(non_snake_case)]
+                struct RaceKeepRest< $( $F ),* > {
+                    $( $F: Option<$F> ),*
+                }
+
+                impl< $( $F ),* > Future for RaceKeepRest< $( $F ),* >
+                where
+                    $( $F: Future + Unpin ),*
+                {
+                    type Output = ($Either< $( $F::Output ),* >, ( $( Option<$F> ),* ));
+
+                    fn poll(
+                        mut self: core::pin::Pin<&mut Self>,
+                        cx: &mut core::task::Context<'_>,
+                    ) -> core::task::Poll<Self::Output> {
+                        let this = &mut *self;
+                        let mut winner = None;
+                        $(
+                            if winner.is_none() {
+                                if let Some(future) = &mut this.$F {
+                                    if let core::task::Poll::Ready(x) = trace_poll!("race_keep_rest", stringify!($Nth), core::pin::Pin::new(future).poll(cx)) {
+                                        this.$F = None;
+                                        log_event!("race resolved by branch {}", stringify!($Nth));
+                                        winner = Some($Either::$Nth(x));
+                                    }
+                                }
+                            }
+                        )*
+
+                        match winner {
+                            Some(winner) => core::task::Poll::Ready((winner, ( $( this.$F.take() ),* ))),
+                            None => core::task::Poll::Pending,
+                        }
+                    }
+                }
+
+                #[cfg(feature = "futures-core")]
+                impl< $( $F ),* > futures_core::FusedFuture for RaceKeepRest< $( $F ),* >
+                where
+                    $( $F: Future + Unpin ),*
+                {
+                    fn is_terminated(&self) -> bool {
+                        $( self.$F.is_none() )&&*
+                    }
+                }
+
+                #[allow(non_snake_case)]
+                let ( $( $F ),* ) = self;
+
+                RaceKeepRest {
+                    $( $F: Some($F) ),*
+                }
+            }
+        }
+
+        impl<T, $( $F ),* > RaceSame for ( $( $F ),* )
+        where
+            $( $F: Future<Output = T> ),*
+        {
+            type Output = T;
+
+            fn race_same(self) -> combinator_future!(Self::Output) {
+                #[allow(non_snake_case)]
+                struct RaceSame< $( $F ),* > {
+                    $( $F: $F, )*
+                    done: bool,
+                }
+
+                impl<T, $( $F ),* > Future for RaceSame< $( $F ),* >
+                where
+                    $( $F: Future<Output = T> ),*
+                {
+                    type Output = T;
+
+                    fn poll(
+                        self: core::pin::Pin<&mut Self>,
+                        cx: &mut core::task::Context<'_>,
+                    ) -> core::task::Poll<Self::Output> {
+                        let this = unsafe { self.get_unchecked_mut() };
+                        if this.done {
+                            return core::task::Poll::Pending;
+                        }
+
+                        $(
+                            if let core::task::Poll::Ready(x) = trace_poll!("race_same", stringify!($Nth), unsafe { core::pin::Pin::new_unchecked(&mut this.$F) }.poll(cx)) {
+                                log_event!("race_same resolved by branch {}", stringify!($Nth));
+                                this.done = true;
+                                return core::task::Poll::Ready(x);
+                            }
+                        )*
+
+                        core::task::Poll::Pending
+                    }
+                }
+
+                #[cfg(feature = "futures-core")]
+                impl<T, $( $F ),* > futures_core::FusedFuture for RaceSame< $( $F ),* >
+                where
+                    $( $F: Future<Output = T> ),*
+                {
+                    fn is_terminated(&self) -> bool {
+                        self.done
+                    }
+                }
+
+                #[allow(non_snake_case)]
+                let ( $( $F ),* ) = self;
+
+                RaceSame {
+                    $( $F, )*
+                    done: false,
+                }
+            }
+
+            fn race_same_indexed(self) -> combinator_future!((usize, T)) {
+                Map::new(self.race(), $Either::into_indexed)
+            }
+        }
+
+        impl<T, $( $F ),* > FirstN for ( $( $F ),* )
+        where
+            $( $F: Future<Output = T> ),*
+        {
+            type Output = T;
+
+            fn first_n<const K: usize>(self) -> combinator_future!([(usize, T); K]) {
+                #[allow(non_snake_case)]
+                struct FirstN<T, const K: usize, $( $F ),* > {
+                    $( $F: Option<$F>, )*
+                    collected: Collected<T, K>,
+                    done: bool,
+                }
+
+                impl<T, const K: usize, $( $F ),* > Future for FirstN<T, K, $( $F ),* >
+                where
+                    $( $F: Future<Output = T> ),*
+                {
+                    type Output = [(usize, T); K];
+
+                    #[allow(unused_assignments)]
+                    fn poll(
+                        self: core::pin::Pin<&mut Self>,
+                        cx: &mut core::task::Context<'_>,
+                    ) -> core::task::Poll<Self::Output> {
+                        let this = unsafe { self.get_unchecked_mut() };
+                        if this.done {
+                            return core::task::Poll::Pending;
+                        }
+
+                        let mut index = 0;
+                        $(
+                            if !this.collected.is_full() {
+                                if let Some(future) = &mut this.$F {
+                                    if let core::task::Poll::Ready(x) = trace_poll!("first_n", stringify!($Nth), unsafe { core::pin::Pin::new_unchecked(future) }.poll(cx)) {
+                                        this.$F = None;
+                                        this.collected.push(index, x);
+                                    }
+                                }
+                            }
+                            index += 1;
+                        )*
+
+                        if this.collected.is_full() {
+                            log_event!("first_n resolved");
+                            this.done = true;
+                            core::task::Poll::Ready(this.collected.take())
+                        } else {
+                            core::task::Poll::Pending
+                        }
+                    }
+                }
+
+                #[cfg(feature = "futures-core")]
+                impl<T, const K: usize, $( $F ),* > futures_core::FusedFuture for FirstN<T, K, $( $F ),* >
+                where
+                    $( $F: Future<Output = T> ),*
+                {
+                    fn is_terminated(&self) -> bool {
+                        self.done
+                    }
+                }
+
+                const { assert!(K <= [ $( replace!($F, ()) ),* ].len(), "can't wait for more futures than there are") };
+
+                #[allow(non_snake_case)]
+                let ( $( $F ),* ) = self;
+
+                FirstN {
+                    $( $F: Some($F), )*
+                    collected: Collected::new(),
+                    done: false,
+                }
+            }
+        }
+
+        impl<T, E, $( $F ),* > Quorum for ( $( $F ),* )
+        where
+            $( $F: Future<Output = Result<T, E>> ),*
+        {
+            type Ok = T;
+            type Errors = [Option<E>; [ $( replace!($F, ()) ),* ].len()];
+
+            fn quorum<const K: usize>(self) -> combinator_future!(Result<[(usize, T); K], Self::Errors>) {
+                const N: usize = [ $( replace!($F, ()) ),* ].len();
+
+                #[allow(non_snake_case)]
+                struct Quorum<T, E, const K: usize, $( $F ),* > {
+                    $( $F: Option<$F>, )*
+                    tally: Tally<T, E, K, N>,
+                    done: bool,
+                }
+
+                impl<T, E, const K: usize, $( $F ),* > Future for Quorum<T, E, K, $( $F ),* >
+                where
+                    $( $F: Future<Output = Result<T, E>> ),*
+                {
+                    type Output = Result<[(usize, T); K], [Option<E>; N]>;
+
+                    #[allow(unused_assignments)]
+                    fn poll(
+                        self: core::pin::Pin<&mut Self>,
+                        cx: &mut core::task::Context<'_>,
+                    ) -> core::task::Poll<Self::Output> {
+                        let this = unsafe { self.get_unchecked_mut() };
+                        if this.done {
+                            return core::task::Poll::Pending;
+                        }
+
+                        let mut index = 0;
+                        $(
+                            if !this.tally.is_decided() {
+                                if let Some(future) = &mut this.$F {
+                                    if let core::task::Poll::Ready(x) = trace_poll!("quorum", stringify!($Nth), unsafe { core::pin::Pin::new_unchecked(future) }.poll(cx)) {
+                                        this.$F = None;
+                                        this.tally.record(index, x);
+                                    }
+                                }
+                            }
+                            index += 1;
+                        )*
+
+                        if this.tally.is_decided() {
+                            this.done = true;
+                            core::task::Poll::Ready(this.tally.take())
+                        } else {
+                            core::task::Poll::Pending
+                        }
+                    }
+                }
+
+                #[cfg(feature = "futures-core")]
+                impl<T, E, const K: usize, $( $F ),* > futures_core::FusedFuture for Quorum<T, E, K, $( $F ),* >
+                where
+                    $( $F: Future<Output = Result<T, E>> ),*
+                {
+                    fn is_terminated(&self) -> bool {
+                        self.done
+                    }
+                }
+
+                const { assert!(K <= N, "can't wait for more futures than there are") };
+
+                #[allow(non_snake_case)]
+                let ( $( $F ),* ) = self;
+
+                Quorum {
+                    $( $F: Some($F), )*
+                    tally: Tally::new(),
+                    done: false,
+                }
+            }
+        }
+
+        impl< $( $F ),* > JoinNow for ( $( $F ),* )
+        where
+            $( $F: Future ),*
+        {
+            type Output = ( $( Option<$F::Output> ),* );
+
+            fn join_now(self) -> Self::Output {
+                let mut cx = core::task::Context::from_waker(core::task::Waker::noop());
+
+                #[allow(non_snake_case)]
+                let ( $( $F ),* ) = self;
+
+                ( $( match core::pin::pin!($F).poll(&mut cx) {
+                    core::task::Poll::Ready(x) => Some(x),
+                    core::task::Poll::Pending => None,
+                } ),* )
+            }
+        }
+
+        impl< $( $F ),* > RaceNow for ( $( $F ),* )
+        where
+            $( $F: Future ),*
+        {
+            type Output = Option<$Either< $( $F::Output ),* >>;
+
+            fn race_now(self) -> Self::Output {
+                let mut cx = core::task::Context::from_waker(core::task::Waker::noop());
+
+                #[allow(non_snake_case)]
+                let ( $( $F ),* ) = self;
+
+                $(
+                    if let core::task::Poll::Ready(x) = core::pin::pin!($F).poll(&mut cx) {
+                        return Some($Either::$Nth(x));
+                    }
+                )*
+
+                None
+            }
+        }
+
+        // The variant names double as the closures' output types here.
+        impl< $( $F, $A, $Nth ),* > JoinFn for ( $( ($F, $A) ),* )
+        where
+            $( $F: AsyncFnOnce($A) -> $Nth ),*
+        {
+            type Output = ( $( $Nth ),* );
+
+            fn join_fn(self) -> combinator_future!(Self::Output) {
+                #[allow(non_snake_case)]
+                let ( $( $F ),* ) = self;
+
+                ( $( async move { let (f, args) = $F; f(args).await } ),* ).join()
+            }
+        }
+
+        impl< $( $F, $A, $Nth ),* > RaceFn for ( $( ($F, $A) ),* )
+        where
+            $( $F: AsyncFnOnce($A) -> $Nth ),*
+        {
+            type Output = $Either< $( $Nth ),* >;
+
+            fn race_fn(self) -> combinator_future!(Self::Output) {
+                #[allow(non_snake_case)]
+                let ( $( $F ),* ) = self;
+
+                ( $( async move { let (f, args) = $F; f(args).await } ),* ).race()
+            }
+        }
+    };
+}
+
+impl_combinators!(Either, F0: A0: First: map_first, F1: A1: Second: map_second);
+impl_combinators!(Either3, F0: A0: First: map_first, F1: A1: Second: map_second, F2: A2: Third: map_third);
+impl_combinators!(Either4, F0: A0: First: map_first, F1: A1: Second: map_second, F2: A2: Third: map_third, F3: A3: Fourth: map_fourth);
+#[cfg(feature = "arity-8")]
+impl_combinators!(Either5, F0: A0: First: map_first, F1: A1: Second: map_second, F2: A2: Third: map_third, F3: A3: Fourth: map_fourth, F4: A4: Fifth: map_fifth);
+#[cfg(feature = "arity-8")]
+impl_combinators!(Either6, F0: A0: First: map_first, F1: A1: Second: map_second, F2: A2: Third: map_third, F3: A3: Fourth: map_fourth, F4: A4: Fifth: map_fifth, F5: A5: Sixth: map_sixth);
+#[cfg(feature = "arity-8")]
+impl_combinators!(Either7, F0: A0: First: map_first, F1: A1: Second: map_second, F2: A2: Third: map_third, F3: A3: Fourth: map_fourth, F4: A4: Fifth: map_fifth, F5: A5: Sixth: map_sixth, F6: A6: Seventh: map_seventh);
+#[cfg(feature = "arity-8")]
+impl_combinators!(Either8, F0: A0: First: map_first, F1: A1: Second: map_second, F2: A2: Third: map_third, F3: A3: Fourth: map_fourth, F4: A4: Fifth: map_fifth, F5: A5: Sixth: map_sixth, F6: A6: Seventh: map_seventh, F7: A7: Eighth: map_eighth);
+#[cfg(feature = "arity-16")]
+impl_combinators!(Either9, F0: A0: First: map_first, F1: A1: Second: map_second, F2: A2: Third: map_third, F3: A3: Fourth: map_fourth, F4: A4: Fifth: map_fifth, F5: A5: Sixth: map_sixth, F6: A6: Seventh: map_seventh, F7: A7: Eighth: map_eighth, F8: A8: Ninth: map_ninth);
+#[cfg(feature = "arity-16")]
+impl_combinators!(Either10, F0: A0: First: map_first, F1: A1: Second: map_second, F2: A2: Third: map_third, F3: A3: Fourth: map_fourth, F4: A4: Fifth: map_fifth, F5: A5: Sixth: map_sixth, F6: A6: Seventh: map_seventh, F7: A7: Eighth: map_eighth, F8: A8: Ninth: map_ninth, F9: A9: Tenth: map_tenth);
+#[cfg(feature = "arity-16")]
+impl_combinators!(Either11, F0: A0: First: map_first, F1: A1: Second: map_second, F2: A2: Third: map_third, F3: A3: Fourth: map_fourth, F4: A4: Fifth: map_fifth, F5: A5: Sixth: map_sixth, F6: A6: Seventh: map_seventh, F7: A7: Eighth: map_eighth, F8: A8: Ninth: map_ninth, F9: A9: Tenth: map_tenth, F10: A10: Eleventh: map_eleventh);
+#[cfg(feature = "arity-16")]
+impl_combinators!(Either12, F0: A0: First: map_first, F1: A1: Second: map_second, F2: A2: Third: map_third, F3: A3: Fourth: map_fourth, F4: A4: Fifth: map_fifth, F5: A5: Sixth: map_sixth, F6: A6: Seventh: map_seventh, F7: A7: Eighth: map_eighth, F8: A8: Ninth: map_ninth, F9: A9: Tenth: map_tenth, F10: A10: Eleventh: map_eleventh, F11: A11: Twelfth: map_twelfth);
+#[cfg(feature = "arity-16")]
+impl_combinators!(Either13, F0: A0: First: map_first, F1: A1: Second: map_second, F2: A2: Third: map_third, F3: A3: Fourth: map_fourth, F4: A4: Fifth: map_fifth, F5: A5: Sixth: map_sixth, F6: A6: Seventh: map_seventh, F7: A7: Eighth: map_eighth, F8: A8: Ninth: map_ninth, F9: A9: Tenth: map_tenth, F10: A10: Eleventh: map_eleventh, F11: A11: Twelfth: map_twelfth, F12: A12: Thirteenth: map_thirteenth);
+#[cfg(feature = "arity-16")]
+impl_combinators!(Either14, F0: A0: First: map_first, F1: A1: Second: map_second, F2: A2: Third: map_third, F3: A3: Fourth: map_fourth, F4: A4: Fifth: map_fifth, F5: A5: Sixth: map_sixth, F6: A6: Seventh: map_seventh, F7: A7: Eighth: map_eighth, F8: A8: Ninth: map_ninth, F9: A9: Tenth: map_tenth, F10: A10: Eleventh: map_eleventh, F11: A11: Twelfth: map_twelfth, F12: A12: Thirteenth: map_thirteenth, F13: A13: Fourteenth: map_fourteenth);
+#[cfg(feature = "arity-16")]
+impl_combinators!(Either15, F0: A0: First: map_first, F1: A1: Second: map_second, F2: A2: Third: map_third, F3: A3: Fourth: map_fourth, F4: A4: Fifth: map_fifth, F5: A5: Sixth: map_sixth, F6: A6: Seventh: map_seventh, F7: A7: Eighth: map_eighth, F8: A8: Ninth: map_ninth, F9: A9: Tenth: map_tenth, F10: A10: Eleventh: map_eleventh, F11: A11: Twelfth: map_twelfth, F12: A12: Thirteenth: map_thirteenth, F13: A13: Fourteenth: map_fourteenth, F14: A14: Fifteenth: map_fifteenth);
+#[cfg(feature = "arity-16")]
+impl_combinators!(Either16, F0: A0: First: map_first, F1: A1: Second: map_second, F2: A2: Third: map_third, F3: A3: Fourth: map_fourth, F4: A4: Fifth: map_fifth, F5: A5: Sixth: map_sixth, F6: A6: Seventh: map_seventh, F7: A7: Eighth: map_eighth, F8: A8: Ninth: map_ninth, F9: A9: Tenth: map_tenth, F10: A10: Eleventh: map_eleventh, F11: A11: Twelfth: map_twelfth, F12: A12: Thirteenth: map_thirteenth, F13: A13: Fourteenth: map_fourteenth, F14: A14: Fifteenth: map_fifteenth, F15: A15: Sixteenth: map_sixteenth);
+
+/// Generates a widening `From` conversion from a smaller `EitherN` into a
+/// larger one.
+macro_rules! impl_widen {
+    (
+        $Small: ident [ $( $SF: ident : $SNth: ident ),* ],
+        $Big: ident [ $( $BF: ident : $BNth: ident ),* ]
+    ) => {
+        impl< $( $BF ),* > From<$Small< $( $SF ),* >> for $Big< $( $BF ),* > {
+            fn from(either: $Small< $( $SF ),* >) -> Self {
+                match either {
+                    $( $Small::$SNth(x) => Self::$SNth(x), )*
+                }
+            }
+        }
+    };
+}
+
+/// Generates widening conversions from every `EitherN` into every larger one,
+/// working through the family from smallest to largest.
+macro_rules! impl_widening {
+    ( [ $( $Small: ident $small: tt )* ] ) => {};
+    (
+        [ $( $Small: ident $small: tt )* ]
+        $( #[$attr: meta] )* $Big: ident $big: tt $( $rest: tt )*
+    ) => {
+        $( #[$attr] )*
+        const _: () = {
+            $( impl_widen!($Small $small, $Big $big); )*
+        };
+        impl_widening!([ $( $Small $small )* $Big $big ] $( $rest )*);
+    };
+}
+
+impl_widening!(
+    []
+    Either [F0: First, F1: Second]
+    Either3 [F0: First, F1: Second, F2: Third]
+    Either4 [F0: First, F1: Second, F2: Third, F3: Fourth]
+    #[cfg(feature = "arity-8")]
+    Either5 [F0: First, F1: Second, F2: Third, F3: Fourth, F4: Fifth]
+    #[cfg(feature = "arity-8")]
+    Either6 [F0: First, F1: Second, F2: Third, F3: Fourth, F4: Fifth, F5: Sixth]
+    #[cfg(feature = "arity-8")]
+    Either7 [F0: First, F1: Second, F2: Third, F3: Fourth, F4: Fifth, F5: Sixth, F6: Seventh]
+    #[cfg(feature = "arity-8")]
+    Either8 [F0: First, F1: Second, F2: Third, F3: Fourth, F4: Fifth, F5: Sixth, F6: Seventh, F7: Eighth]
+    #[cfg(feature = "arity-16")]
+    Either9 [F0: First, F1: Second, F2: Third, F3: Fourth, F4: Fifth, F5: Sixth, F6: Seventh, F7: Eighth, F8: Ninth]
+    #[cfg(feature = "arity-16")]
+    Either10 [F0: First, F1: Second, F2: Third, F3: Fourth, F4: Fifth, F5: Sixth, F6: Seventh, F7: Eighth, F8: Ninth, F9: Tenth]
+    #[cfg(feature = "arity-16")]
+    Either11 [F0: First, F1: Second, F2: Third, F3: Fourth, F4: Fifth, F5: Sixth, F6: Seventh, F7: Eighth, F8: Ninth, F9: Tenth, F10: Eleventh]
+    #[cfg(feature = "arity-16")]
+    Either12 [F0: First, F1: Second, F2: Third, F3: Fourth, F4: Fifth, F5: Sixth, F6: Seventh, F7: Eighth, F8: Ninth, F9: Tenth, F10: Eleventh, F11: Twelfth]
+    #[cfg(feature = "arity-16")]
+    Either13 [F0: First, F1: Second, F2: Third, F3: Fourth, F4: Fifth, F5: Sixth, F6: Seventh, F7: Eighth, F8: Ninth, F9: Tenth, F10: Eleventh, F11: Twelfth, F12: Thirteenth]
+    #[cfg(feature = "arity-16")]
+    Either14 [F0: First, F1: Second, F2: Third, F3: Fourth, F4: Fifth, F5: Sixth, F6: Seventh, F7: Eighth, F8: Ninth, F9: Tenth, F10: Eleventh, F11: Twelfth, F12: Thirteenth, F13: Fourteenth]
+    #[cfg(feature = "arity-16")]
+    Either15 [F0: First, F1: Second, F2: Third, F3: Fourth, F4: Fifth, F5: Sixth, F6: Seventh, F7: Eighth, F8: Ninth, F9: Tenth, F10: Eleventh, F11: Twelfth, F12: Thirteenth, F13: Fourteenth, F14: Fifteenth]
+    #[cfg(feature = "arity-16")]
+    Either16 [F0: First, F1: Second, F2: Third, F3: Fourth, F4: Fifth, F5: Sixth, F6: Seventh, F7: Eighth, F8: Ninth, F9: Tenth, F10: Eleventh, F11: Twelfth, F12: Thirteenth, F13: Fourteenth, F14: Fifteenth, F15: Sixteenth]
+);
+
+impl<A, B> Either<A, B> {
+    /// Convert into a `Result`, treating `First` as `Ok` and `Second` as `Err`.
+    ///
+    /// # Errors
+    ///
+    /// Returns the value as an `Err` if this is `Second`.
+    pub fn into_result(self) -> Result<A, B> {
+        self.ok_first()
+    }
+
+    /// Convert from a `Result`, where `Ok` becomes `First` and `Err` becomes
+    /// `Second`.
+    pub fn from_result(result: Result<A, B>) -> Self {
+        match result {
+            Ok(a) => Self::First(a),
+            Err(b) => Self::Second(b),
+        }
+    }
+
+    /// Convert into a `Result`, treating `First` as `Ok` and `Second` as `Err`.
+    ///
+    /// # Errors
+    ///
+    /// Returns the value as an `Err` if this is `Second`.
+    pub fn ok_first(self) -> Result<A, B> {
+        match self {
+            Self::First(a) => Ok(a),
+            Self::Second(b) => Err(b),
+        }
+    }
+
+    /// Convert into a `Result`, treating `Second` as `Ok` and `First` as `Err`.
+    ///
+    /// # Errors
+    ///
+    /// Returns the value as an `Err` if this is `First`.
+    ///
+    /// ```rust
+    /// use woven::Race;
+    ///
+    /// # #[derive(Debug, PartialEq)]
+    /// # struct Cancelled;
+    /// async fn work() -> Result<u32, Cancelled> {
+    ///     let cancelled = async { Cancelled };
+    ///     let work = async { 42 };
+    ///
+    ///     // The cancellation branch is the error path.
+    ///     let value = (cancelled, work).race().await.ok_second()?;
+    ///     Ok(value)
+    /// }
+    ///
+    /// cassette::block_on(async {
+    ///     assert_eq!(work().await, Err(Cancelled));
+    /// });
+    /// ```
+    pub fn ok_second(self) -> Result<B, A> {
+        match self {
+            Self::First(a) => Err(a),
+            Self::Second(b) => Ok(b),
+        }
+    }
+}
+
+/// `First` is treated as `Ok`, and `Second` as `Err`.
+impl<A, B> From<Either<A, B>> for Result<A, B> {
+    fn from(either: Either<A, B>) -> Self {
+        either.into_result()
+    }
+}
+
+/// `Ok` becomes `First`, and `Err` becomes `Second`.
+impl<A, B> From<Result<A, B>> for Either<A, B> {
+    fn from(result: Result<A, B>) -> Self {
+        Either::from_result(result)
+    }
+}
@@ -0,0 +1,173 @@
+//! Measuring how much work a future causes its executor.
+//!
+//! Woven doesn't implement granular wakers, so a large join or race is polled
+//! in full whenever any of its futures wake it. Wrapping it in [`measure`]
+//! counts how often that happens, and how long it takes to complete.
+//!
+//! The wakes themselves aren't counted, since that would mean handing the
+//! future a waker pointing into the borrowed [`Metrics`], which could outlive
+//! it.
+//!
+//! ```rust
+//! use core::time::Duration;
+//! use woven::metrics::{measure, Metrics};
+//! use woven::time::Clock;
+//! use woven::Join;
+//!
+//! struct Frozen;
+//!
+//! impl Clock for Frozen {
+//!     fn now(&self) -> Duration {
+//!         Duration::ZERO
+//!     }
+//! }
+//!
+//! let metrics = Metrics::named("fan-out");
+//!
+//! cassette::block_on(async {
+//!     measure((async { 1 }, async { 2 }).join(), &Frozen, &metrics).await;
+//! });
+//!
+//! let report = metrics.report();
+//! assert_eq!(report.label, Some("fan-out"));
+//! assert_eq!(report.polls, 1);
+//! assert_eq!(report.time_to_completion, Some(Duration::ZERO));
+//! ```
+
+use core::cell::Cell;
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+use core::time::Duration;
+
+use crate::time::Clock;
+
+/// A snapshot of the statistics gathered by [`Metrics`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Report {
+    /// The label given with [`Metrics::named`], if any.
+    pub label: Option<&'static str>,
+    /// The number of times the future was polled.
+    pub polls: u32,
+    /// The time from the first poll until the future completed, or `None` if
+    /// it hasn't completed yet.
+    pub time_to_completion: Option<Duration>,
+}
+
+/// Statistics gathered about a [`measure`]d future.
+#[derive(Debug, Default)]
+pub struct Metrics {
+    report: Cell<Report>,
+}
+
+impl Metrics {
+    /// Create a new set of empty statistics.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self::with_label(None)
+    }
+
+    /// Create a new set of empty statistics, labelled so reports can be
+    /// attributed to a particular future.
+    #[must_use]
+    pub const fn named(label: &'static str) -> Self {
+        Self::with_label(Some(label))
+    }
+
+    const fn with_label(label: Option<&'static str>) -> Self {
+        Self {
+            report: Cell::new(Report {
+                label,
+                polls: 0,
+                time_to_completion: None,
+            }),
+        }
+    }
+
+    /// The statistics gathered so far.
+    pub fn report(&self) -> Report {
+        self.report.get()
+    }
+
+    /// Clear the statistics gathered so far, keeping the label.
+    pub fn reset(&self) {
+        self.report.set(Report {
+            label: self.report.get().label,
+            ..Report::default()
+        });
+    }
+
+    fn update(&self, f: impl FnOnce(&mut Report)) {
+        let mut report = self.report.get();
+        f(&mut report);
+        self.report.set(report);
+    }
+}
+
+/// Wrap a future, recording statistics about how it's polled in `metrics`.
+///
+/// Measures can be nested and share a [`Metrics`], each poll of each measure
+/// counting towards the total.
+///
+/// ```rust
+/// use core::time::Duration;
+/// use woven::metrics::{measure, Metrics};
+/// use woven::time::Clock;
+///
+/// struct Frozen;
+///
+/// impl Clock for Frozen {
+///     fn now(&self) -> Duration {
+///         Duration::ZERO
+///     }
+/// }
+///
+/// let metrics = Metrics::new();
+///
+/// cassette::block_on(measure(measure(async {}, &Frozen, &metrics), &Frozen, &metrics));
+///
+/// assert_eq!(metrics.report().polls, 2);
+/// ```
+pub fn measure<'a, F: Future, C: Clock>(
+    future: F,
+    clock: &'a C,
+    metrics: &'a Metrics,
+) -> Measure<'a, F, C> {
+    Measure {
+        future,
+        clock,
+        metrics,
+        started: None,
+    }
+}
+
+/// The future returned by [`measure`].
+pub struct Measure<'a, F, C> {
+    future: F,
+    clock: &'a C,
+    metrics: &'a Metrics,
+    /// When the future was first polled.
+    started: Option<Duration>,
+}
+
+impl<F: Future, C: Clock> Future for Measure<'_, F, C> {
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = unsafe { self.get_unchecked_mut() };
+        let started = *this.started.get_or_insert_with(|| this.clock.now());
+        this.metrics
+            .update(|report| report.polls = report.polls.saturating_add(1));
+
+        let poll = unsafe { Pin::new_unchecked(&mut this.future) }.poll(cx);
+
+        if poll.is_ready() {
+            let elapsed = this.clock.now().saturating_sub(started);
+            this.metrics
+                .update(|report| report.time_to_completion = Some(elapsed));
+        }
+
+        poll
+    }
+}
@@ -0,0 +1,26 @@
+//! The combinator and extension traits, and the macros, for glob importing.
+//!
+//! ```rust
+//! use woven::prelude::*;
+//!
+//! cassette::block_on(async {
+//!     let (a, b) = (async { 1 }, async { 2 }).join().await;
+//!     assert_eq!(a + b, 3);
+//!
+//!     let winner = (async { 1 }, core::future::pending::<u8>()).race_same().await;
+//!     assert_eq!(winner, 1);
+//! });
+//! ```
+
+pub use crate::stream::{Stream, StreamExt};
+pub use crate::{compose, select};
+pub use crate::{
+    Choice, CompletionStream, FirstN, Join, JoinFn, JoinLimited, JoinNow, JoinProgress, JoinWith,
+    PipeInto, Quorum, Race, RaceAllReady, RaceFn, RaceKeepRest, RaceMap, RaceNow, RaceSame,
+    UniformChoice,
+};
+
+#[cfg(feature = "embedded-io")]
+pub use crate::io::{ReadTimeoutExt, WriteTimeoutExt};
+#[cfg(feature = "nightly-variadic")]
+pub use crate::{cons, cons_pat};
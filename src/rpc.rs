@@ -0,0 +1,196 @@
+//! Request/response correlation over any channel.
+//!
+//! An [`Rpc`] hands each [`call`](Rpc::call) an [`Id`] to send alongside its
+//! request, and the side receiving responses passes them back with
+//! [`respond`](Rpc::respond), which wakes the call waiting for that `Id`. The
+//! requests and responses themselves travel over whatever transport the
+//! caller chooses.
+//!
+//! ```rust
+//! use woven::channel::priority::Priority;
+//! use woven::rpc::{Id, Rpc};
+//! use woven::{Either, Join, Race};
+//!
+//! static REQUESTS: Priority<(Id, u32), 4> = Priority::new();
+//! static RESPONSES: Priority<(Id, u32), 4> = Priority::new();
+//! static DOUBLER: Rpc<u32, 4> = Rpc::new();
+//!
+//! cassette::block_on(async {
+//!     let server = async {
+//!         let (id, x) = REQUESTS.recv().await;
+//!         RESPONSES.send((id, x * 2)).await;
+//!     };
+//!
+//!     let dispatcher = async {
+//!         loop {
+//!             let (id, response) = RESPONSES.recv().await;
+//!             let _ = DOUBLER.respond(id, response);
+//!         }
+//!     };
+//!
+//!     let client = DOUBLER.call(async |id| REQUESTS.send((id, 21)).await);
+//!
+//!     let result = ((client, server).join(), dispatcher).race().await;
+//!     assert!(matches!(result, Either::First((42, ()))));
+//! });
+//! ```
+
+use core::task::Poll;
+
+use crate::sync::{Mutex, WakerSet, WakerSlot};
+
+/// Identifies which call a response belongs to.
+///
+/// Ids count up with each call, so a late response to a call that stopped
+/// waiting can't be mistaken for a later call's until the count wraps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Id(u32);
+
+impl Id {
+    /// The id as a raw integer, for sending over a transport.
+    #[must_use]
+    pub const fn to_bits(self) -> u32 {
+        self.0
+    }
+
+    /// Recreate an id received over a transport.
+    #[must_use]
+    pub const fn from_bits(bits: u32) -> Self {
+        Self(bits)
+    }
+}
+
+enum Slot<R> {
+    Free,
+    Waiting(Id, WakerSlot),
+    Ready(R),
+}
+
+struct State<R, const N: usize> {
+    slots: [Slot<R>; N],
+    /// The id to give the next call.
+    next: u32,
+    /// Calls waiting for a free slot.
+    callers: WakerSet,
+}
+
+/// Correlates responses of type `R` with up to `N` calls in flight at once.
+pub struct Rpc<R, const N: usize> {
+    state: Mutex<State<R, N>>,
+}
+
+impl<R, const N: usize> Rpc<R, N> {
+    /// Create a new correlator with no calls in flight.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            state: Mutex::new(State {
+                slots: [const { Slot::Free }; N],
+                next: 0,
+                callers: WakerSet::new(),
+            }),
+        }
+    }
+
+    /// Make a call, passing its [`Id`] to `send` to send the request, then
+    /// waiting for the matching response.
+    ///
+    /// If `N` calls are already in flight, waits for one of them to complete
+    /// first.
+    pub async fn call(&self, send: impl AsyncFnOnce(Id)) -> R {
+        /// Frees the slot if the call stops waiting.
+        struct Abandon<'a, R, const N: usize> {
+            rpc: &'a Rpc<R, N>,
+            slot: Option<usize>,
+        }
+
+        impl<R, const N: usize> Drop for Abandon<'_, R, N> {
+            fn drop(&mut self) {
+                let Some(slot) = self.slot else {
+                    return;
+                };
+
+                // A response that already arrived is handed back, to be
+                // dropped outside the lock.
+                let unwanted = self.rpc.state.lock(|s| {
+                    let entry = core::mem::replace(&mut s.slots[slot], Slot::Free);
+                    s.callers.wake();
+                    entry
+                });
+                drop(unwanted);
+            }
+        }
+
+        let (slot, id) = core::future::poll_fn(|cx| {
+            self.state.lock(|s| {
+                let Some(slot) = s.slots.iter().position(|e| matches!(e, Slot::Free)) else {
+                    s.callers.register(cx.waker());
+                    return Poll::Pending;
+                };
+
+                let id = Id(s.next);
+                s.next = s.next.wrapping_add(1);
+                s.slots[slot] = Slot::Waiting(id, WakerSlot::new());
+                Poll::Ready((slot, id))
+            })
+        })
+        .await;
+
+        let mut abandon = Abandon {
+            rpc: self,
+            slot: Some(slot),
+        };
+
+        send(id).await;
+
+        core::future::poll_fn(|cx| {
+            self.state.lock(|s| match &mut s.slots[slot] {
+                Slot::Waiting(_, waker) => {
+                    waker.register(cx.waker());
+                    Poll::Pending
+                }
+                entry @ Slot::Ready(_) => {
+                    let Slot::Ready(response) = core::mem::replace(entry, Slot::Free) else {
+                        impossible!()
+                    };
+                    abandon.slot = None;
+                    s.callers.wake();
+                    Poll::Ready(response)
+                }
+                Slot::Free => impossible!(),
+            })
+        })
+        .await
+    }
+
+    /// Deliver a response to the call waiting for it.
+    ///
+    /// # Errors
+    ///
+    /// Returns the response back if no call is waiting for `id`, because it
+    /// was never issued, was already answered, or its call stopped waiting.
+    pub fn respond(&self, id: Id, response: R) -> Result<(), R> {
+        self.state.lock(|s| {
+            let Some(entry) = s
+                .slots
+                .iter_mut()
+                .find(|e| matches!(e, Slot::Waiting(i, _) if *i == id))
+            else {
+                return Err(response);
+            };
+
+            if let Slot::Waiting(_, mut waker) = core::mem::replace(entry, Slot::Ready(response)) {
+                waker.wake();
+            }
+
+            Ok(())
+        })
+    }
+}
+
+impl<R, const N: usize> Default for Rpc<R, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,9 @@
+//! Sealing the combinator traits.
+
+/// A supertrait of the combinator traits, which can't be named outside this
+/// crate and so keeps them from being implemented elsewhere.
+///
+/// The futures they return gain a `FusedFuture` bound with the `futures-core`
+/// feature, which would break outside implementations whenever another crate
+/// in the build enabled it.
+pub trait Sealed {}
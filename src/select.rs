@@ -0,0 +1,358 @@
+//! The [`select!`](crate::select) macro.
+
+use crate::sync::atomic::{AtomicUsize, Ordering};
+
+/// Wait on multiple futures at once, running the branch of whichever completes
+/// first. The others are dropped.
+///
+/// Each branch is written `pattern = future => body`, and the whole macro
+/// evaluates to the body of the branch that won. It must be used inside an
+/// async context. Since the bodies aren't closures, they can `return`, `break`
+/// or use `?` on behalf of the surrounding code.
+///
+/// Each time the select is polled, it starts from a different branch, so that
+/// a branch that's always ready can't starve the others. A leading `biased;`
+/// polls the futures in the order they're written instead, so if more than one
+/// is ready the first wins, such as when giving priority to a shutdown signal.
+///
+/// ```rust
+/// use woven::select;
+///
+/// cassette::block_on(async {
+///     let (mut a, mut b) = (0, 0);
+///     for _ in 0..10 {
+///         select! {
+///             () = async {} => a += 1,
+///             () = async {} => b += 1,
+///         }
+///     }
+///     assert!(a > 0 && b > 0);
+///
+///     let shutdown = core::future::pending::<()>();
+///     let request = async { 7 };
+///
+///     let handled = select! {
+///         biased;
+///         () = shutdown => None,
+///         n = request => Some(n * 2),
+///     };
+///     assert_eq!(handled, Some(14));
+/// });
+/// ```
+///
+/// If a future's output doesn't match its branch's pattern, that branch is
+/// disabled and the others keep being waited on. This makes streams easy to
+/// mix in, as `Some(item) = stream.next()` stops being considered once the
+/// stream ends. Panics if every branch ends up disabled. Here `biased;` makes
+/// sure the loop only breaks once the stream is done.
+///
+/// ```rust
+/// use woven::select;
+/// use woven::stream::{generate, Slot, StreamExt};
+///
+/// cassette::block_on(async {
+///     let slot = Slot::new();
+///     let mut events = core::pin::pin!(generate(&slot, |y| async move {
+///         y.emit(1).await;
+///         y.emit(2).await;
+///     }));
+///
+///     let mut seen = 0;
+///     loop {
+///         select! {
+///             biased;
+///             Some(event) = events.next() => seen += event,
+///             () = async {} => break,
+///         }
+///     }
+///     assert_eq!(seen, 3);
+/// });
+/// ```
+///
+/// The output is only moved into the pattern once it's known to match, so
+/// bindings can be `mut`.
+///
+/// ```rust
+/// use woven::select;
+///
+/// cassette::block_on(async {
+///     let greeting = select! {
+///         biased;
+///         Some(s) = async { None::<String> } => s,
+///         Some(mut s) = async { Some(String::from("hello")) } => {
+///             s.push('!');
+///             s
+///         }
+///     };
+///     assert_eq!(greeting, "hello!");
+/// });
+/// ```
+///
+/// A branch can be given a guard with `, if condition` after its future. The
+/// branch is disabled from the start if the guard is false, though its future
+/// is still created. An `else` branch at the end makes the select
+/// non-blocking: it's taken if no branch is ready when first polled, or if
+/// every branch is disabled.
+///
+/// ```rust
+/// use woven::select;
+///
+/// cassette::block_on(async {
+///     let mut queue = vec![1, 2];
+///     let busy = true;
+///
+///     let next = select! {
+///         () = core::future::pending(), if !busy => None,
+///         n = async { queue.pop() } => n,
+///         else => None,
+///     };
+///     assert_eq!(next, Some(2));
+///
+///     let next = select! {
+///         n = core::future::pending::<u32>() => Some(n),
+///         else => None,
+///     };
+///     assert_eq!(next, None);
+/// });
+/// ```
+#[macro_export]
+macro_rules! select {
+    (biased; $( $tokens: tt )*) => {
+        $crate::__select!(@parse biased [] $( $tokens )*)
+    };
+    ($( $tokens: tt )*) => {
+        $crate::__select!(@parse fair [] $( $tokens )*)
+    };
+}
+
+/// The implementation of [`select!`].
+///
+/// Branches are parsed one at a time, then each is paired with its `EitherN`
+/// variant and its position while counting up to the right `EitherN`. The
+/// variant names double as the names of each branch's pinned future.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __select {
+    (@parse $mode: tt [ $( $branches: tt )* ] else => $else: expr $(,)?) => {
+        $crate::__select!(@dispatch $mode [ $( $branches )* ] [ $else ])
+    };
+    (@parse $mode: tt [ $( $branches: tt )* ]) => {
+        $crate::__select!(@dispatch $mode [ $( $branches )* ] [])
+    };
+    // A pattern can't be captured with a `pat` fragment, since it has to be
+    // taken apart again by `@check`, so its tokens are collected up to the `=`.
+    (@parse $mode: tt [ $( $branches: tt )* ] $( $tokens: tt )+) => {
+        $crate::__select!(@pattern $mode [ $( $branches )* ] [] $( $tokens )+)
+    };
+    (
+        @pattern $mode: tt [ $( $branches: tt )* ] [ $( $pat: tt )+ ]
+        = $future: expr, if $guard: expr => $body: expr $( , $( $rest: tt )* )?
+    ) => {
+        $crate::__select!(
+            @parse $mode [ $( $branches )* ([ $( $pat )+ ], $future, $body, [ $guard ]) ]
+            $( $( $rest )* )?
+        )
+    };
+    (
+        @pattern $mode: tt [ $( $branches: tt )* ] [ $( $pat: tt )+ ]
+        = $future: expr => $body: expr $( , $( $rest: tt )* )?
+    ) => {
+        $crate::__select!(
+            @parse $mode [ $( $branches )* ([ $( $pat )+ ], $future, $body, []) ]
+            $( $( $rest )* )?
+        )
+    };
+    (@pattern $mode: tt $branches: tt [ $( $pat: tt )* ] $next: tt $( $rest: tt )*) => {
+        $crate::__select!(@pattern $mode $branches [ $( $pat )* $next ] $( $rest )*)
+    };
+
+    (@dispatch $mode: tt [] [ $else: expr ]) => {
+        $else
+    };
+    // There's no single variant `EitherN`, so pad with a branch that's never
+    // enabled.
+    (@dispatch $mode: tt [ $branch: tt ] [ $( $else: expr )? ]) => {
+        $crate::__select!(
+            @dispatch $mode
+            [ $branch ([_], ::core::future::pending::<()>(), ::core::unreachable!(), [ false ]) ]
+            [ $( $else )? ]
+        )
+    };
+    (@dispatch $mode: tt [ $( $branches: tt )+ ] [ $( $else: expr )? ]) => {
+        $crate::__select!(
+            @name $mode []
+            [_ _ Either Either3 Either4 Either5 Either6 Either7 Either8 Either9 Either10 Either11 Either12 Either13 Either14 Either15 Either16]
+            [First Second Third Fourth Fifth Sixth Seventh Eighth Ninth Tenth Eleventh Twelfth Thirteenth Fourteenth Fifteenth Sixteenth]
+            [0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15]
+            [ $( $else )? ]
+            $( $branches )+
+        )
+    };
+
+    (@start biased $branches: expr) => {
+        0
+    };
+    (@start fair $branches: expr) => {
+        $crate::__select_start($branches)
+    };
+
+    (@non_blocking) => {
+        false
+    };
+    (@non_blocking $else: expr) => {
+        true
+    };
+    (@else) => {
+        ::core::panic!("all branches of select! are disabled, and there's no else branch")
+    };
+    (@else $else: expr) => {
+        $else
+    };
+
+    // A branch's pattern with its `ref` and `mut` binding modes stripped, so
+    // it can be checked against a reference to the output without moving out
+    // of it. Groups are descended into by pushing what's around them onto a
+    // stack of frames.
+    (@check [] [ $( $done: tt )* ]) => {
+        $( $done )*
+    };
+    (@check [ (() [ $( $outer: tt )* ] [ $( $rest: tt )* ]) $( $frames: tt )* ] [ $( $done: tt )* ]) => {
+        $crate::__select!(@check [ $( $frames )* ] [ $( $outer )* ( $( $done )* ) ] $( $rest )*)
+    };
+    (@check [ ([] [ $( $outer: tt )* ] [ $( $rest: tt )* ]) $( $frames: tt )* ] [ $( $done: tt )* ]) => {
+        $crate::__select!(@check [ $( $frames )* ] [ $( $outer )* [ $( $done )* ] ] $( $rest )*)
+    };
+    (@check [ ({} [ $( $outer: tt )* ] [ $( $rest: tt )* ]) $( $frames: tt )* ] [ $( $done: tt )* ]) => {
+        $crate::__select!(@check [ $( $frames )* ] [ $( $outer )* { $( $done )* } ] $( $rest )*)
+    };
+    (@check $frames: tt [ $( $done: tt )* ] & mut $( $rest: tt )*) => {
+        $crate::__select!(@check $frames [ $( $done )* & mut ] $( $rest )*)
+    };
+    (@check $frames: tt $done: tt mut $( $rest: tt )*) => {
+        $crate::__select!(@check $frames $done $( $rest )*)
+    };
+    (@check $frames: tt $done: tt ref $( $rest: tt )*) => {
+        $crate::__select!(@check $frames $done $( $rest )*)
+    };
+    (@check [ $( $frames: tt )* ] $done: tt ( $( $inner: tt )* ) $( $rest: tt )*) => {
+        $crate::__select!(@check [ (() $done [ $( $rest )* ]) $( $frames )* ] [] $( $inner )*)
+    };
+    (@check [ $( $frames: tt )* ] $done: tt [ $( $inner: tt )* ] $( $rest: tt )*) => {
+        $crate::__select!(@check [ ([] $done [ $( $rest )* ]) $( $frames )* ] [] $( $inner )*)
+    };
+    (@check [ $( $frames: tt )* ] $done: tt { $( $inner: tt )* } $( $rest: tt )*) => {
+        $crate::__select!(@check [ ({} $done [ $( $rest )* ]) $( $frames )* ] [] $( $inner )*)
+    };
+    (@check $frames: tt [ $( $done: tt )* ] $next: tt $( $rest: tt )*) => {
+        $crate::__select!(@check $frames [ $( $done )* $next ] $( $rest )*)
+    };
+
+    (
+        @name $mode: tt [ $( $done: tt )* ]
+        [ $_skip: tt $( $either: tt )* ]
+        [ $Nth: ident $( $nths: ident )* ]
+        [ $index: literal $( $indices: literal )* ]
+        $else: tt
+        $branch: tt $( $branches: tt )*
+    ) => {
+        $crate::__select!(
+            @name $mode [ $( $done )* ($Nth, $index, $branch) ]
+            [ $( $either )* ]
+            [ $( $nths )* ]
+            [ $( $indices )* ]
+            $else
+            $( $branches )*
+        )
+    };
+    (
+        @name $mode: tt
+        [ $( ($Nth: ident, $index: literal, ([ $( $pat: tt )+ ], $future: expr, $body: expr, [ $( $guard: expr )? ])) )* ]
+        [ $Either: ident $( $either: tt )* ]
+        [ $( $nths: ident )* ]
+        [ $( $indices: literal )* ]
+        [ $( $else: expr )? ]
+    ) => {{
+        // Guards are checked before any future is created, in case they look
+        // at something the futures borrow.
+        $(
+            #[allow(non_snake_case)]
+            let $Nth: bool = true $( && $guard )?;
+        )*
+        $(
+            #[allow(non_snake_case)]
+            let mut $Nth = ($Nth, ::core::pin::pin!($future));
+        )*
+        $(
+            #[allow(non_snake_case)]
+            let mut $Nth = if $Nth.0 {
+                ::core::option::Option::Some($Nth.1.as_mut())
+            } else {
+                ::core::option::Option::None
+            };
+        )*
+
+        // Find the first completed future whose output matches its pattern,
+        // disabling those that don't. The branches from `start` onwards are
+        // polled in the first pass, and those before it in the second.
+        let output = loop {
+            let output = ::core::future::poll_fn(|cx| {
+                let start: usize = $crate::__select!(@start $mode [ $( $index ),* ].len());
+                for first_pass in [true, false] {
+                    $(
+                        if ($index >= start) == first_pass {
+                            if let ::core::option::Option::Some(future) = &mut $Nth {
+                                if let ::core::task::Poll::Ready(x) = ::core::future::Future::poll(future.as_mut(), cx) {
+                                    return ::core::task::Poll::Ready(::core::option::Option::Some($crate::$Either::$Nth(x)));
+                                }
+                            }
+                        }
+                    )*
+                }
+
+                if $crate::__select!(@non_blocking $( $else )?) || $( $Nth.is_none() )&&* {
+                    ::core::task::Poll::Ready(::core::option::Option::None)
+                } else {
+                    ::core::task::Poll::Pending
+                }
+            })
+            .await;
+
+            match output {
+                $(
+                    ::core::option::Option::Some($crate::$Either::$Nth(x)) => {
+                        #[allow(unused_variables)]
+                        match &x {
+                            $crate::__select!(@check [] [] $( $pat )+) => break ::core::option::Option::Some($crate::$Either::$Nth(x)),
+                            #[allow(unreachable_patterns)]
+                            _ => $Nth = ::core::option::Option::None,
+                        }
+                    }
+                )*
+                ::core::option::Option::None => break ::core::option::Option::None,
+            }
+        };
+
+        match output {
+            $( ::core::option::Option::Some($crate::$Either::$Nth($( $pat )+)) => $body, )*
+            ::core::option::Option::None => $crate::__select!(@else $( $else )?),
+            #[allow(unreachable_patterns)]
+            _ => ::core::unreachable!(),
+        }
+    }};
+}
+
+/// Where a non-`biased` [`select!`](crate::select) with the given number of
+/// branches starts polling.
+///
+/// This just cycles through the branches. The counter is updated with a
+/// separate load and store, since not every target has compare-and-swap, but
+/// losing an update only means repeating a start position.
+#[doc(hidden)]
+#[must_use]
+pub fn __select_start(branches: usize) -> usize {
+    static NEXT: AtomicUsize = AtomicUsize::new(0);
+
+    let start = NEXT.load(Ordering::Relaxed);
+    NEXT.store(start.wrapping_add(1), Ordering::Relaxed);
+    start % branches
+}
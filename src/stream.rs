@@ -0,0 +1,839 @@
+//! Asynchronous streams of values.
+//!
+//! ```rust
+//! use core::pin::Pin;
+//! use core::task::{Context, Poll};
+//! use woven::stream::{Stream, StreamExt};
+//!
+//! struct Countdown(u32);
+//!
+//! impl Stream for Countdown {
+//!     type Item = u32;
+//!
+//!     fn poll_next(mut self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Option<u32>> {
+//!         let n = self.0;
+//!         self.0 = n.saturating_sub(1);
+//!         Poll::Ready((n > 0).then_some(n))
+//!     }
+//! }
+//!
+//! cassette::block_on(async {
+//!     let mut countdown = Countdown(2);
+//!     assert_eq!(countdown.next().await, Some(2));
+//!     assert_eq!(countdown.next().await, Some(1));
+//!     assert_eq!(countdown.next().await, None);
+//! });
+//! ```
+//!
+//! An `EitherN` of streams is itself a stream, so the source can be picked at
+//! runtime.
+//!
+//! ```rust
+//! # use core::pin::Pin;
+//! # use core::task::{Context, Poll};
+//! use woven::stream::{Stream, StreamExt};
+//! use woven::Either;
+//! # struct Countdown(u32);
+//! # impl Stream for Countdown {
+//! #     type Item = u32;
+//! #     fn poll_next(mut self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Option<u32>> {
+//! #         let n = self.0;
+//! #         self.0 = n.saturating_sub(1);
+//! #         Poll::Ready((n > 0).then_some(n))
+//! #     }
+//! # }
+//!
+//! let mut stream: Either<Countdown, Countdown> = Either::Second(Countdown(1));
+//!
+//! cassette::block_on(async {
+//!     assert_eq!(stream.next().await, Some(1));
+//!     assert_eq!(stream.next().await, None);
+//! });
+//! ```
+
+use core::cell::{Cell, RefCell};
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+
+use crate::channel::ring::Ring;
+use crate::channel::Lagged;
+use crate::sync::WakerSlot;
+
+/// An asynchronous sequence of values.
+pub trait Stream {
+    /// The type of value produced by the stream.
+    type Item;
+
+    /// Attempt to get the next value, registering the current task for wakeup
+    /// if it isn't ready yet. `None` means the stream has ended.
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>>;
+}
+
+impl<S: Stream + Unpin + ?Sized> Stream for &mut S {
+    type Item = S::Item;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut **self).poll_next(cx)
+    }
+}
+
+impl<P> Stream for Pin<P>
+where
+    P: core::ops::DerefMut,
+    P::Target: Stream,
+{
+    type Item = <P::Target as Stream>::Item;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        unsafe { self.get_unchecked_mut() }.as_mut().poll_next(cx)
+    }
+}
+
+/// Convenience methods for [`Stream`]s.
+pub trait StreamExt: Stream {
+    /// Get the next value from the stream, or `None` if it has ended.
+    fn next(&mut self) -> Next<'_, Self>
+    where
+        Self: Unpin,
+    {
+        Next { stream: self }
+    }
+}
+
+impl<S: Stream + ?Sized> StreamExt for S {}
+
+/// The future returned by [`StreamExt::next`].
+pub struct Next<'a, S: ?Sized> {
+    stream: &'a mut S,
+}
+
+impl<S: Stream + Unpin + ?Sized> Future for Next<'_, S> {
+    type Output = Option<S::Item>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        Pin::new(&mut *self.stream).poll_next(cx)
+    }
+}
+
+/// Where a [`generate`]d stream's values are passed from the generating future
+/// to the stream.
+pub struct Slot<T>(Cell<Option<T>>);
+
+impl<T> Slot<T> {
+    /// Create a new, empty slot.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self(Cell::new(None))
+    }
+}
+
+impl<T> Default for Slot<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The handle a [`generate`]d stream's future uses to emit values.
+pub struct Yielder<'a, T> {
+    slot: &'a Slot<T>,
+}
+
+impl<T> Clone for Yielder<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Yielder<'_, T> {}
+
+impl<T> Yielder<'_, T> {
+    /// Emit a value from the stream, resolving once the stream's consumer has
+    /// taken it.
+    pub async fn emit(&self, value: T) {
+        let mut value = Some(value);
+
+        core::future::poll_fn(|cx| match value.take() {
+            Some(v) => {
+                if let Some(other) = self.slot.0.replace(Some(v)) {
+                    // Another emit is waiting to be taken, wait our turn.
+                    value = self.slot.0.replace(Some(other));
+                    cx.waker().wake_by_ref();
+                }
+                Poll::Pending
+            }
+            None => Poll::Ready(()),
+        })
+        .await;
+    }
+}
+
+/// Create a stream from a future that emits values through a [`Yielder`],
+/// letting streams be written as straight-line async code. The stream ends
+/// when the future completes.
+///
+/// ```rust
+/// use woven::stream::{generate, Slot, StreamExt};
+///
+/// cassette::block_on(async {
+///     let slot = Slot::new();
+///     let mut evens = core::pin::pin!(generate(&slot, |y| async move {
+///         for i in 0..3 {
+///             y.emit(i * 2).await;
+///         }
+///     }));
+///
+///     assert_eq!(evens.next().await, Some(0));
+///     assert_eq!(evens.next().await, Some(2));
+///     assert_eq!(evens.next().await, Some(4));
+///     assert_eq!(evens.next().await, None);
+/// });
+/// ```
+pub fn generate<'a, T, F, Fut>(slot: &'a Slot<T>, f: F) -> Generate<'a, T, Fut>
+where
+    F: FnOnce(Yielder<'a, T>) -> Fut,
+    Fut: Future<Output = ()>,
+{
+    Generate {
+        slot,
+        future: Some(f(Yielder { slot })),
+    }
+}
+
+/// The stream returned by [`generate`].
+pub struct Generate<'a, T, Fut> {
+    slot: &'a Slot<T>,
+    /// `None` once the future has completed.
+    future: Option<Fut>,
+}
+
+impl<T, Fut: Future<Output = ()>> Stream for Generate<'_, T, Fut> {
+    type Item = T;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = unsafe { self.get_unchecked_mut() };
+
+        let Some(future) = &mut this.future else {
+            return Poll::Ready(None);
+        };
+
+        if let Poll::Ready(()) = unsafe { Pin::new_unchecked(future) }.poll(cx) {
+            this.future = None;
+        }
+
+        match this.slot.0.take() {
+            Some(value) => Poll::Ready(Some(value)),
+            None if this.future.is_none() => Poll::Ready(None),
+            None => Poll::Pending,
+        }
+    }
+}
+
+/// What a [`Tee`] does when an item arrives while one of its outputs' buffers
+/// is full.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum SlowConsumer {
+    /// The source isn't polled until the slowest output has caught up.
+    Wait,
+    /// The output's oldest buffered item is discarded to make room, and it
+    /// gets a [`Lagged`] error on its next poll.
+    DropOldest,
+    /// The output's buffer is discarded, and it gets a [`Lagged`] error on its
+    /// next poll and then ends.
+    Disconnect,
+}
+
+struct TeeState<T, const CAP: usize, const OUTS: usize> {
+    buffers: [Ring<T, CAP>; OUTS],
+    /// The number of items each output has missed since it last polled.
+    lagged: [u64; OUTS],
+    /// Outputs that have been dropped or disconnected.
+    disconnected: [bool; OUTS],
+    wakers: [WakerSlot; OUTS],
+    /// Whether the source stream has ended.
+    ended: bool,
+}
+
+/// Split a stream into `OUTS` outputs that each see every item, buffering up
+/// to `CAP` items for outputs that fall behind the others. Fails to compile if
+/// `CAP` is zero.
+///
+/// ```rust
+/// use woven::stream::{tee, SlowConsumer, Stream, StreamExt};
+/// # use core::pin::Pin;
+/// # use core::task::{Context, Poll};
+/// # struct Countdown(u32);
+/// # impl Stream for Countdown {
+/// #     type Item = u32;
+/// #     fn poll_next(mut self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Option<u32>> {
+/// #         let n = self.0;
+/// #         self.0 = n.saturating_sub(1);
+/// #         Poll::Ready((n > 0).then_some(n))
+/// #     }
+/// # }
+///
+/// let mut events = tee::<_, 2, 2>(Countdown(3), SlowConsumer::Wait);
+/// let [mut fast, mut slow] = events.split();
+///
+/// cassette::block_on(async {
+///     assert_eq!(fast.next().await, Some(Ok(3)));
+///     assert_eq!(fast.next().await, Some(Ok(2)));
+///
+///     // `slow` has two items buffered, so `fast` must wait for it to catch up.
+///     assert_eq!(slow.next().await, Some(Ok(3)));
+///     assert_eq!(slow.next().await, Some(Ok(2)));
+///     assert_eq!(fast.next().await, Some(Ok(1)));
+///     assert_eq!(fast.next().await, None);
+///     assert_eq!(slow.next().await, Some(Ok(1)));
+///     assert_eq!(slow.next().await, None);
+/// });
+/// ```
+pub fn tee<S, const CAP: usize, const OUTS: usize>(
+    stream: S,
+    policy: SlowConsumer,
+) -> Tee<S, CAP, OUTS>
+where
+    S: Stream + Unpin,
+    S::Item: Clone,
+{
+    Tee {
+        stream: RefCell::new(stream),
+        policy,
+        state: RefCell::new(TeeState {
+            buffers: [const { Ring::new() }; OUTS],
+            lagged: [0; OUTS],
+            disconnected: [false; OUTS],
+            wakers: [const { WakerSlot::new() }; OUTS],
+            ended: false,
+        }),
+    }
+}
+
+/// A stream split into several outputs, returned by [`tee`].
+///
+/// The source is polled by whichever output needs its next item, which passes
+/// a copy to the others.
+pub struct Tee<S: Stream, const CAP: usize, const OUTS: usize> {
+    stream: RefCell<S>,
+    policy: SlowConsumer,
+    state: RefCell<TeeState<S::Item, CAP, OUTS>>,
+}
+
+impl<S, const CAP: usize, const OUTS: usize> Tee<S, CAP, OUTS>
+where
+    S: Stream + Unpin,
+    S::Item: Clone,
+{
+    /// Get the outputs.
+    pub fn split(&mut self) -> [TeeOutput<'_, S, CAP, OUTS>; OUTS] {
+        core::array::from_fn(|id| TeeOutput { tee: self, id })
+    }
+
+    fn poll_output(
+        &self,
+        id: usize,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<S::Item, Lagged>>> {
+        let mut state = self.state.borrow_mut();
+        let s = &mut *state;
+
+        let lagged = core::mem::take(&mut s.lagged[id]);
+        if lagged > 0 {
+            return Poll::Ready(Some(Err(Lagged(lagged))));
+        }
+
+        let was_full = s.buffers[id].is_full();
+        if let Some(item) = s.buffers[id].pop_front() {
+            if was_full {
+                for waker in &mut s.wakers {
+                    waker.wake();
+                }
+            }
+            return Poll::Ready(Some(Ok(item)));
+        }
+
+        if s.disconnected[id] || s.ended {
+            return Poll::Ready(None);
+        }
+
+        let blocked = self.policy == SlowConsumer::Wait
+            && (0..OUTS).any(|other| !s.disconnected[other] && s.buffers[other].is_full());
+        if blocked {
+            s.wakers[id].register(cx.waker());
+            return Poll::Pending;
+        }
+
+        let poll = Pin::new(&mut *self.stream.borrow_mut()).poll_next(cx);
+        match poll {
+            Poll::Ready(Some(item)) => {
+                for other in 0..OUTS {
+                    if other == id || s.disconnected[other] {
+                        continue;
+                    }
+
+                    let buffer = &mut s.buffers[other];
+                    if buffer.is_full() {
+                        match self.policy {
+                            SlowConsumer::Wait => impossible!(),
+                            SlowConsumer::DropOldest => {
+                                buffer.pop_front();
+                                s.lagged[other] += 1;
+                            }
+                            SlowConsumer::Disconnect => {
+                                while buffer.pop_front().is_some() {
+                                    s.lagged[other] += 1;
+                                }
+                                s.lagged[other] += 1;
+                                s.disconnected[other] = true;
+                                s.wakers[other].wake();
+                                continue;
+                            }
+                        }
+                    }
+
+                    if buffer.push_back(item.clone()).is_err() {
+                        impossible!();
+                    }
+                    s.wakers[other].wake();
+                }
+
+                Poll::Ready(Some(Ok(item)))
+            }
+            Poll::Ready(None) => {
+                s.ended = true;
+                for waker in &mut s.wakers {
+                    waker.wake();
+                }
+                Poll::Ready(None)
+            }
+            Poll::Pending => {
+                s.wakers[id].register(cx.waker());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+/// One output of a [`Tee`].
+pub struct TeeOutput<'a, S: Stream, const CAP: usize, const OUTS: usize> {
+    tee: &'a Tee<S, CAP, OUTS>,
+    id: usize,
+}
+
+/// Yields every item of the source stream, or [`Lagged`] if some were missed.
+impl<S, const CAP: usize, const OUTS: usize> Stream for TeeOutput<'_, S, CAP, OUTS>
+where
+    S: Stream + Unpin,
+    S::Item: Clone,
+{
+    type Item = Result<S::Item, Lagged>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.tee.poll_output(self.id, cx)
+    }
+}
+
+impl<S: Stream, const CAP: usize, const OUTS: usize> Drop for TeeOutput<'_, S, CAP, OUTS> {
+    fn drop(&mut self) {
+        let mut state = self.tee.state.borrow_mut();
+        state.disconnected[self.id] = true;
+        while state.buffers[self.id].pop_front().is_some() {}
+
+        // Outputs waiting on this one to catch up can carry on.
+        for waker in &mut state.wakers {
+            waker.wake();
+        }
+    }
+}
+
+struct Route<K, T, const CAP: usize> {
+    key: K,
+    buffer: Ring<T, CAP>,
+    waker: WakerSlot,
+}
+
+struct DemuxState<K, T, const CAP: usize, const KEYS: usize> {
+    /// One entry per output slot, `None` when the slot is free.
+    routes: [Option<Route<K, T, CAP>>; KEYS],
+    /// An item taken from the source whose output's buffer was full, along
+    /// with the output it's for.
+    stashed: Option<(usize, T)>,
+    /// Whether the source stream has ended.
+    ended: bool,
+}
+
+impl<K, T, const CAP: usize, const KEYS: usize> DemuxState<K, T, CAP, KEYS> {
+    fn wake_all(&mut self) {
+        for route in self.routes.iter_mut().flatten() {
+            route.waker.wake();
+        }
+    }
+
+    /// Try to hand the stashed item to its output, returning whether the stash
+    /// is now empty.
+    fn unstash(&mut self) -> bool {
+        let Some((slot, item)) = self.stashed.take() else {
+            return true;
+        };
+
+        let Some(route) = &mut self.routes[slot] else {
+            return true;
+        };
+
+        match route.buffer.push_back(item) {
+            Ok(()) => {
+                route.waker.wake();
+                true
+            }
+            Err(item) => {
+                self.stashed = Some((slot, item));
+                false
+            }
+        }
+    }
+}
+
+/// Route the items of a stream to separate outputs by key, buffering up to
+/// `CAP` items for each of up to `KEYS` outputs.
+///
+/// Items whose key has no output are discarded. When an output's buffer is
+/// full the source isn't polled again until it has room, so one slow output
+/// holds up the rest. Fails to compile if `CAP` is zero.
+///
+/// ```rust
+/// use woven::stream::{demux, Stream, StreamExt};
+/// # use core::pin::Pin;
+/// # use core::task::{Context, Poll};
+/// # struct Packets(&'static [(u8, char)]);
+/// # impl Stream for Packets {
+/// #     type Item = (u8, char);
+/// #     fn poll_next(mut self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Option<(u8, char)>> {
+/// #         let (first, rest) = self.0.split_first().map_or((None, &[][..]), |(f, r)| (Some(*f), r));
+/// #         self.0 = rest;
+/// #         Poll::Ready(first)
+/// #     }
+/// # }
+///
+/// let packets = Packets(&[(1, 'a'), (2, 'x'), (1, 'b'), (3, 'q'), (2, 'y')]);
+/// let sessions = demux::<_, _, _, 4, 2>(packets, |&(session, _)| session);
+///
+/// let mut one = sessions.subscribe(1).unwrap();
+/// let mut two = sessions.subscribe(2).unwrap();
+///
+/// cassette::block_on(async {
+///     assert_eq!(two.next().await, Some((2, 'x')));
+///     assert_eq!(one.next().await, Some((1, 'a')));
+///     assert_eq!(one.next().await, Some((1, 'b')));
+///     assert_eq!(one.next().await, None);
+///     assert_eq!(two.next().await, Some((2, 'y')));
+///     assert_eq!(two.next().await, None);
+/// });
+/// ```
+pub fn demux<S, K, F, const CAP: usize, const KEYS: usize>(
+    stream: S,
+    key: F,
+) -> Demux<S, K, F, CAP, KEYS>
+where
+    S: Stream + Unpin,
+    K: PartialEq,
+    F: Fn(&S::Item) -> K,
+{
+    Demux {
+        stream: RefCell::new(stream),
+        key,
+        state: RefCell::new(DemuxState {
+            routes: [const { None }; KEYS],
+            stashed: None,
+            ended: false,
+        }),
+    }
+}
+
+/// A stream split into outputs by key, returned by [`demux`].
+///
+/// The source is polled by whichever output needs its next item, which
+/// buffers the items it reads for the other outputs.
+pub struct Demux<S: Stream, K, F, const CAP: usize, const KEYS: usize> {
+    stream: RefCell<S>,
+    key: F,
+    state: RefCell<DemuxState<K, S::Item, CAP, KEYS>>,
+}
+
+impl<S, K, F, const CAP: usize, const KEYS: usize> Demux<S, K, F, CAP, KEYS>
+where
+    S: Stream + Unpin,
+    K: PartialEq,
+    F: Fn(&S::Item) -> K,
+{
+    /// Get an output receiving every item with the given key from now on.
+    ///
+    /// Returns `None` if the key already has an output, or there are already
+    /// `KEYS` outputs.
+    pub fn subscribe(&self, key: K) -> Option<DemuxOutput<'_, S, K, F, CAP, KEYS>> {
+        let mut state = self.state.borrow_mut();
+
+        let taken = state.routes.iter().flatten().any(|route| route.key == key);
+        let slot = state.routes.iter().position(Option::is_none)?;
+        if taken {
+            return None;
+        }
+
+        state.routes[slot] = Some(Route {
+            key,
+            buffer: Ring::new(),
+            waker: WakerSlot::new(),
+        });
+
+        Some(DemuxOutput { demux: self, slot })
+    }
+
+    fn poll_output(&self, slot: usize, cx: &mut Context<'_>) -> Poll<Option<S::Item>> {
+        let mut state = self.state.borrow_mut();
+        let s = &mut *state;
+
+        let Some(route) = &mut s.routes[slot] else {
+            impossible!()
+        };
+
+        let was_full = route.buffer.is_full();
+        if let Some(item) = route.buffer.pop_front() {
+            if was_full && s.stashed.is_some() {
+                s.unstash();
+                s.wake_all();
+            }
+            return Poll::Ready(Some(item));
+        }
+
+        loop {
+            if s.ended {
+                return Poll::Ready(None);
+            }
+
+            if !s.unstash() {
+                break;
+            }
+
+            let poll = Pin::new(&mut *self.stream.borrow_mut()).poll_next(cx);
+            let item = match poll {
+                Poll::Ready(Some(item)) => item,
+                Poll::Ready(None) => {
+                    s.ended = true;
+                    s.wake_all();
+                    return Poll::Ready(None);
+                }
+                Poll::Pending => break,
+            };
+
+            let key = (self.key)(&item);
+            let Some(target) = s
+                .routes
+                .iter()
+                .position(|route| route.as_ref().is_some_and(|route| route.key == key))
+            else {
+                log_event!("demux discarded an item with no output");
+                continue;
+            };
+
+            if target == slot {
+                return Poll::Ready(Some(item));
+            }
+
+            s.stashed = Some((target, item));
+        }
+
+        if let Some(route) = &mut s.routes[slot] {
+            route.waker.register(cx.waker());
+        }
+        Poll::Pending
+    }
+}
+
+/// One output of a [`Demux`].
+pub struct DemuxOutput<'a, S: Stream, K, F, const CAP: usize, const KEYS: usize> {
+    demux: &'a Demux<S, K, F, CAP, KEYS>,
+    slot: usize,
+}
+
+/// Yields every item of the source stream with this output's key.
+impl<S, K, F, const CAP: usize, const KEYS: usize> Stream for DemuxOutput<'_, S, K, F, CAP, KEYS>
+where
+    S: Stream + Unpin,
+    K: PartialEq,
+    F: Fn(&S::Item) -> K,
+{
+    type Item = S::Item;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.demux.poll_output(self.slot, cx)
+    }
+}
+
+impl<S: Stream, K, F, const CAP: usize, const KEYS: usize> Drop
+    for DemuxOutput<'_, S, K, F, CAP, KEYS>
+{
+    fn drop(&mut self) {
+        let mut state = self.demux.state.borrow_mut();
+        state.routes[self.slot] = None;
+        if state
+            .stashed
+            .as_ref()
+            .is_some_and(|&(slot, _)| slot == self.slot)
+        {
+            state.stashed = None;
+        }
+
+        // Outputs waiting on this one to make room can carry on.
+        state.wake_all();
+    }
+}
+
+/// Merge a slice of streams into one yielding `(index, item)` from whichever
+/// produces next.
+///
+/// The streams are polled in rotation, starting after the one that last
+/// produced an item, so a busy stream can't starve the others. Each stream is
+/// replaced with `None` once it ends, freeing its slot to be refilled, and the
+/// merged stream ends once every slot is empty.
+///
+/// ```rust
+/// use woven::stream::{select_all, Stream, StreamExt};
+/// # use core::pin::Pin;
+/// # use core::task::{Context, Poll};
+/// # struct Countdown(u32);
+/// # impl Stream for Countdown {
+/// #     type Item = u32;
+/// #     fn poll_next(mut self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Option<u32>> {
+/// #         let n = self.0;
+/// #         self.0 = n.saturating_sub(1);
+/// #         Poll::Ready((n > 0).then_some(n))
+/// #     }
+/// # }
+///
+/// let mut connections = [Some(Countdown(2)), None, Some(Countdown(1))];
+///
+/// cassette::block_on(async {
+///     let mut merged = select_all(&mut connections);
+///     assert_eq!(merged.next().await, Some((0, 2)));
+///     assert_eq!(merged.next().await, Some((2, 1)));
+///     assert_eq!(merged.next().await, Some((0, 1)));
+///     assert_eq!(merged.next().await, None);
+/// });
+///
+/// assert!(connections.iter().all(Option::is_none));
+/// ```
+pub fn select_all<S: Stream + Unpin>(streams: &mut [Option<S>]) -> SelectAll<'_, S> {
+    SelectAll { streams, next: 0 }
+}
+
+/// The stream returned by [`select_all`].
+pub struct SelectAll<'a, S> {
+    streams: &'a mut [Option<S>],
+    /// The stream to poll first.
+    next: usize,
+}
+
+impl<S: Stream + Unpin> Stream for SelectAll<'_, S> {
+    type Item = (usize, S::Item);
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        let len = this.streams.len();
+
+        for offset in 0..len {
+            let index = (this.next + offset) % len;
+            let Some(stream) = &mut this.streams[index] else {
+                continue;
+            };
+
+            match Pin::new(stream).poll_next(cx) {
+                Poll::Ready(Some(item)) => {
+                    this.next = (index + 1) % len;
+                    return Poll::Ready(Some((index, item)));
+                }
+                Poll::Ready(None) => {
+                    log_event!("select_all stream {} ended", index);
+                    this.streams[index] = None;
+                }
+                Poll::Pending => {}
+            }
+        }
+
+        if this.streams.iter().all(Option::is_none) {
+            Poll::Ready(None)
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+/// Adapt an [`AsyncIterator`](core::async_iter::AsyncIterator) into a
+/// [`Stream`].
+#[cfg(feature = "async-iterator")]
+pub fn from_async_iter<I: core::async_iter::AsyncIterator>(iter: I) -> FromAsyncIter<I> {
+    FromAsyncIter { iter }
+}
+
+/// The stream returned by [`from_async_iter`].
+#[cfg(feature = "async-iterator")]
+pub struct FromAsyncIter<I> {
+    iter: I,
+}
+
+#[cfg(feature = "async-iterator")]
+impl<I: core::async_iter::AsyncIterator> Stream for FromAsyncIter<I> {
+    type Item = I::Item;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        unsafe { self.map_unchecked_mut(|s| &mut s.iter) }.poll_next(cx)
+    }
+}
+
+/// Adapt a [`Stream`] into an
+/// [`AsyncIterator`](core::async_iter::AsyncIterator).
+///
+/// ```rust
+/// #![feature(async_iterator)]
+///
+/// use core::async_iter::AsyncIterator;
+/// use woven::stream::{from_async_iter, into_async_iter, StreamExt};
+/// # use core::pin::Pin;
+/// # use core::task::{Context, Poll};
+/// # struct Once(Option<u8>);
+/// # impl woven::stream::Stream for Once {
+/// #     type Item = u8;
+/// #     fn poll_next(mut self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Option<u8>> {
+/// #         Poll::Ready(self.0.take())
+/// #     }
+/// # }
+///
+/// fn assert_async_iter(_: &impl AsyncIterator<Item = u8>) {}
+///
+/// let iter = into_async_iter(Once(Some(1)));
+/// assert_async_iter(&iter);
+///
+/// cassette::block_on(async {
+///     let mut stream = from_async_iter(iter);
+///     assert_eq!(stream.next().await, Some(1));
+///     assert_eq!(stream.next().await, None);
+/// });
+/// ```
+#[cfg(feature = "async-iterator")]
+pub fn into_async_iter<S: Stream>(stream: S) -> IntoAsyncIter<S> {
+    IntoAsyncIter { stream }
+}
+
+/// The async iterator returned by [`into_async_iter`].
+#[cfg(feature = "async-iterator")]
+pub struct IntoAsyncIter<S> {
+    stream: S,
+}
+
+#[cfg(feature = "async-iterator")]
+impl<S: Stream> core::async_iter::AsyncIterator for IntoAsyncIter<S> {
+    type Item = S::Item;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        unsafe { self.map_unchecked_mut(|s| &mut s.stream) }.poll_next(cx)
+    }
+}
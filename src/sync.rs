@@ -0,0 +1,282 @@
+//! Internal synchronisation helpers shared by the channels.
+
+use core::task::Waker;
+
+/// The atomic types used throughout the crate.
+///
+/// With the `portable-atomic` feature these come from [`portable_atomic`], so
+/// targets without native atomic compare-and-swap are supported.
+pub(crate) mod atomic {
+    // Which of these are needed depends on the locking backend.
+    #![allow(unused_imports)]
+
+    #[cfg(not(feature = "portable-atomic"))]
+    pub(crate) use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    #[cfg(feature = "portable-atomic")]
+    pub(crate) use portable_atomic::{AtomicBool, AtomicUsize, Ordering};
+}
+
+/// Storage for the waker of a single waiting task.
+///
+/// Registering a waker replaces the stored one, which belongs to the same task.
+/// Sides of a channel that several tasks can wait on use a [`WakerSet`]
+/// instead.
+pub(crate) struct WakerSlot(Option<Waker>);
+
+impl WakerSlot {
+    pub(crate) const fn new() -> Self {
+        Self(None)
+    }
+
+    pub(crate) fn register(&mut self, waker: &Waker) {
+        match &mut self.0 {
+            Some(current) if current.will_wake(waker) => {}
+            slot => *slot = Some(waker.clone()),
+        }
+    }
+
+    pub(crate) fn wake(&mut self) {
+        if let Some(waker) = self.0.take() {
+            waker.wake();
+        }
+    }
+}
+
+// Only the lock-based channels have sides that several tasks wait on.
+cfg_locking! {
+    /// How many tasks a [`WakerSet`] has room for.
+    const WAITERS: usize = 4;
+
+    /// Storage for the wakers of every task waiting on one side of a channel,
+    /// all of which are woken when it changes.
+    ///
+    /// Once [`WAITERS`] tasks are waiting, registering another evicts and
+    /// wakes one of them, so none miss their wakeup. Beyond that many waiters
+    /// the evicted ones keep re-registering rather than sleeping.
+    pub(crate) struct WakerSet([Option<Waker>; WAITERS]);
+
+    impl WakerSet {
+        pub(crate) const fn new() -> Self {
+            Self([const { None }; WAITERS])
+        }
+
+        pub(crate) fn register(&mut self, waker: &Waker) {
+            if self.0.iter().flatten().any(|w| w.will_wake(waker)) {
+                return;
+            }
+
+            if let Some(free) = self.0.iter_mut().find(|w| w.is_none()) {
+                *free = Some(waker.clone());
+            } else if let Some(evicted) = self.0[0].replace(waker.clone()) {
+                evicted.wake();
+            }
+        }
+
+        pub(crate) fn wake(&mut self) {
+            for waker in self.0.iter_mut().filter_map(Option::take) {
+                waker.wake();
+            }
+        }
+    }
+}
+
+// The lock and interrupt-safe waker need atomic compare-and-swap, or one of the
+// features providing it.
+cfg_locking! {
+    use core::cell::UnsafeCell;
+
+    #[cfg(not(feature = "critical-section"))]
+    use self::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+    /// A lock guarding the brief critical sections inside the channels.
+    ///
+    /// By default this is a tiny spin lock, only ever held for the handful of
+    /// instructions it takes to update a channel's state. With the
+    /// `critical-section` feature it is a [`critical_section`] instead, which is
+    /// the right choice on single-core targets where interrupts are the only
+    /// source of concurrency, since a spin lock would deadlock there.
+    ///
+    /// Some channels run user code (closures, `Clone`, `Ord`) while the lock is
+    /// held. If that code locks the same channel again, the spin lock deadlocks,
+    /// and since critical sections nest, the `critical-section` lock panics rather
+    /// than hand out a second `&mut`, or deadlocks too with the `no-panic`
+    /// feature.
+    pub(crate) struct Mutex<T> {
+        #[cfg(not(feature = "critical-section"))]
+        locked: AtomicBool,
+        /// Whether the lock is held, to catch re-entrant locking.
+        #[cfg(feature = "critical-section")]
+        held: core::cell::Cell<bool>,
+        value: UnsafeCell<T>,
+    }
+
+    unsafe impl<T: Send> Sync for Mutex<T> {}
+
+    impl<T> Mutex<T> {
+        pub(crate) const fn new(value: T) -> Self {
+            Self {
+                #[cfg(not(feature = "critical-section"))]
+                locked: AtomicBool::new(false),
+                #[cfg(feature = "critical-section")]
+                held: core::cell::Cell::new(false),
+                value: UnsafeCell::new(value),
+            }
+        }
+
+        /// Run `f` with exclusive access to the protected value.
+        #[cfg(not(feature = "critical-section"))]
+        pub(crate) fn lock<R>(&self, f: impl FnOnce(&mut T) -> R) -> R {
+            struct Unlock<'a>(&'a AtomicBool);
+
+            impl Drop for Unlock<'_> {
+                fn drop(&mut self) {
+                    self.0.store(false, Ordering::Release);
+                }
+            }
+
+            while self
+                .locked
+                .compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Relaxed)
+                .is_err()
+            {
+                core::hint::spin_loop();
+            }
+
+            let _unlock = Unlock(&self.locked);
+            f(unsafe { &mut *self.value.get() })
+        }
+
+        /// Run `f` with exclusive access to the protected value.
+        ///
+        /// # Panics
+        ///
+        /// Panics if the lock is already held by the caller, unless the
+        /// `no-panic` feature is enabled, in which case it never returns.
+        #[cfg(feature = "critical-section")]
+        pub(crate) fn lock<R>(&self, f: impl FnOnce(&mut T) -> R) -> R {
+            struct Release<'a>(&'a core::cell::Cell<bool>);
+
+            impl Drop for Release<'_> {
+                fn drop(&mut self) {
+                    self.0.set(false);
+                }
+            }
+
+            critical_section::with(|_| {
+                // Only this core can be inside the critical section, so the lock
+                // being held means it was re-entered.
+                #[cfg(not(feature = "no-panic"))]
+                assert!(!self.held.replace(true), "channel locked re-entrantly");
+                #[cfg(feature = "no-panic")]
+                if self.held.replace(true) {
+                    loop {
+                        core::hint::spin_loop();
+                    }
+                }
+                let _release = Release(&self.held);
+
+                // Not re-entered, so this is the only reference.
+                f(unsafe { &mut *self.value.get() })
+            })
+        }
+    }
+
+    #[cfg(not(feature = "critical-section"))]
+    const WAITING: usize = 0;
+    #[cfg(not(feature = "critical-section"))]
+    const REGISTERING: usize = 0b01;
+    #[cfg(not(feature = "critical-section"))]
+    const WAKING: usize = 0b10;
+
+    /// A waker slot that can be registered and woken concurrently without
+    /// locking, for use in interrupt handlers.
+    ///
+    /// Only one task may register at a time, but any number may wake.
+    #[cfg(not(feature = "critical-section"))]
+    pub(crate) struct AtomicWaker {
+        state: AtomicUsize,
+        waker: UnsafeCell<Option<Waker>>,
+    }
+
+    #[cfg(not(feature = "critical-section"))]
+    unsafe impl Send for AtomicWaker {}
+    #[cfg(not(feature = "critical-section"))]
+    unsafe impl Sync for AtomicWaker {}
+
+    #[cfg(not(feature = "critical-section"))]
+    impl AtomicWaker {
+        pub(crate) const fn new() -> Self {
+            Self {
+                state: AtomicUsize::new(WAITING),
+                waker: UnsafeCell::new(None),
+            }
+        }
+
+        pub(crate) fn register(&self, waker: &Waker) {
+            match self
+                .state
+                .compare_exchange(WAITING, REGISTERING, Ordering::Acquire, Ordering::Acquire)
+                .unwrap_or_else(|x| x)
+            {
+                WAITING => {
+                    // We hold the `REGISTERING` bit, so nobody else touches the slot.
+                    let slot = unsafe { &mut *self.waker.get() };
+                    match slot {
+                        Some(current) if current.will_wake(waker) => {}
+                        _ => *slot = Some(waker.clone()),
+                    }
+
+                    if self
+                        .state
+                        .compare_exchange(REGISTERING, WAITING, Ordering::AcqRel, Ordering::Acquire)
+                        .is_err()
+                    {
+                        // A wake arrived while we were registering, it couldn't
+                        // take the waker so we have to wake it ourselves.
+                        let waker = slot.take();
+                        self.state.swap(WAITING, Ordering::AcqRel);
+                        if let Some(waker) = waker {
+                            waker.wake();
+                        }
+                    }
+                }
+                WAKING => waker.wake_by_ref(),
+                _ => {}
+            }
+        }
+
+        pub(crate) fn wake(&self) {
+            if self.state.fetch_or(WAKING, Ordering::AcqRel) == WAITING {
+                let waker = unsafe { (*self.waker.get()).take() };
+                self.state.fetch_and(!WAKING, Ordering::Release);
+                if let Some(waker) = waker {
+                    waker.wake();
+                }
+            }
+        }
+    }
+
+    /// A waker slot that can be registered and woken from interrupt handlers.
+    ///
+    /// With the `critical-section` feature this avoids the compare-and-swap loop,
+    /// and uses a critical section instead.
+    #[cfg(feature = "critical-section")]
+    pub(crate) struct AtomicWaker(Mutex<WakerSlot>);
+
+    #[cfg(feature = "critical-section")]
+    impl AtomicWaker {
+        pub(crate) const fn new() -> Self {
+            Self(Mutex::new(WakerSlot::new()))
+        }
+
+        pub(crate) fn register(&self, waker: &Waker) {
+            self.0.lock(|slot| slot.register(waker));
+        }
+
+        pub(crate) fn wake(&self) {
+            if let Some(waker) = self.0.lock(|slot| slot.0.take()) {
+                waker.wake();
+            }
+        }
+    }
+}
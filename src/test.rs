@@ -0,0 +1,528 @@
+//! Utilities for testing futures without a runtime.
+//!
+//! A [`Step`] polls a future only when asked to, with a waker that counts how
+//! often it was woken, so tests can assert on exactly what happens on each
+//! poll.
+//!
+//! ```rust
+//! use core::task::Poll;
+//! use woven::test::{Step, WakeCounter};
+//! use woven::Join;
+//!
+//! static WAKES: WakeCounter = WakeCounter::new();
+//!
+//! let mut polled = false;
+//! let slow = core::future::poll_fn(move |cx| {
+//!     if polled {
+//!         return Poll::Ready(2);
+//!     }
+//!     polled = true;
+//!     cx.waker().wake_by_ref();
+//!     Poll::Pending
+//! });
+//!
+//! let mut step = core::pin::pin!(Step::new((async { 1 }, slow).join(), &WAKES));
+//!
+//! assert_eq!(step.as_mut().poll(), Poll::Pending);
+//! assert!(step.woken());
+//! assert_eq!(step.as_mut().poll(), Poll::Ready((1, 2)));
+//! assert_eq!(step.polls(), 2);
+//! assert_eq!(WAKES.count(), 1);
+//! ```
+//!
+//! For one-off checks, the [`assert_pending!`](crate::assert_pending),
+//! [`assert_ready!`](crate::assert_ready) and
+//! [`assert_ready_eq!`](crate::assert_ready_eq) macros poll a pinned future
+//! once and assert on the result.
+//!
+//! [`interleavings`] goes further, driving a future through every order in
+//! which the events it waits on could happen, and [`MockClock`] stands in for
+//! the platform's timer so time based behaviour can be tested instantly.
+
+use core::cell::Cell;
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll, Waker};
+
+cfg_locking! {
+    use core::task::{RawWaker, RawWakerVTable};
+    use core::time::Duration;
+
+    use crate::sync::{Mutex, WakerSet};
+    use crate::time::{Clock, Delay};
+}
+
+cfg_locking! {
+    /// Counts how often the wakers it hands out are woken.
+    ///
+    /// Wakers don't carry a lifetime, so the counter must be `'static`, usually by
+    /// declaring it as a `static`.
+    pub struct WakeCounter {
+        wakes: Mutex<usize>,
+    }
+
+    /// The vtable of a [`WakeCounter`]'s wakers.
+    ///
+    /// This is a `static` rather than a `const`, as every waker must point to the
+    /// same vtable for [`Waker::will_wake`] to recognise clones.
+    static VTABLE: RawWakerVTable = RawWakerVTable::new(
+        |data| RawWaker::new(data, &VTABLE),
+        |data| unsafe { &*data.cast::<WakeCounter>() }.wake(),
+        |data| unsafe { &*data.cast::<WakeCounter>() }.wake(),
+        |_| {},
+    );
+
+    impl WakeCounter {
+        /// Create a new counter, which hasn't been woken.
+        #[must_use]
+        pub const fn new() -> Self {
+            Self {
+                wakes: Mutex::new(0),
+            }
+        }
+
+        fn wake(&self) {
+            self.wakes.lock(|wakes| *wakes = wakes.wrapping_add(1));
+        }
+
+        /// A waker that increments this counter when woken.
+        #[must_use]
+        pub fn waker(&'static self) -> Waker {
+            let raw = RawWaker::new(core::ptr::from_ref(self).cast(), &VTABLE);
+            unsafe { Waker::from_raw(raw) }
+        }
+
+        /// The number of times this counter's wakers have been woken.
+        pub fn count(&self) -> usize {
+            self.wakes.lock(|wakes| *wakes)
+        }
+
+        /// Reset the count to zero, returning what it was.
+        pub fn reset(&self) -> usize {
+            self.wakes.lock(core::mem::take)
+        }
+    }
+
+    impl Default for WakeCounter {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    impl core::fmt::Debug for WakeCounter {
+        fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+            f.debug_struct("WakeCounter")
+                .field("wakes", &self.count())
+                .finish()
+        }
+    }
+
+    /// A single-future executor that only polls when asked to.
+    pub struct Step<F> {
+        future: F,
+        wakes: &'static WakeCounter,
+        /// The counter's value as of the last poll.
+        seen: usize,
+        polls: usize,
+    }
+
+    impl<F: Future> Step<F> {
+        /// Wrap a future, waking `wakes` whenever it asks to be polled again.
+        pub fn new(future: F, wakes: &'static WakeCounter) -> Self {
+            Self {
+                future,
+                wakes,
+                seen: wakes.count(),
+                polls: 0,
+            }
+        }
+
+        /// Poll the future once.
+        pub fn poll(self: Pin<&mut Self>) -> Poll<F::Output> {
+            let this = unsafe { self.get_unchecked_mut() };
+            this.seen = this.wakes.count();
+            this.polls += 1;
+
+            let waker = this.wakes.waker();
+            let mut cx = Context::from_waker(&waker);
+            unsafe { Pin::new_unchecked(&mut this.future) }.poll(&mut cx)
+        }
+
+        /// Poll the future up to `n` times, stopping early once it completes.
+        pub fn poll_n(mut self: Pin<&mut Self>, n: usize) -> Poll<F::Output> {
+            for _ in 0..n {
+                if let Poll::Ready(output) = self.as_mut().poll() {
+                    return Poll::Ready(output);
+                }
+            }
+
+            Poll::Pending
+        }
+
+        /// Poll the future until it completes, or until it stops waking itself.
+        pub fn run_until_stalled(mut self: Pin<&mut Self>) -> Poll<F::Output> {
+            loop {
+                if let Poll::Ready(output) = self.as_mut().poll() {
+                    return Poll::Ready(output);
+                }
+
+                if !self.woken() {
+                    return Poll::Pending;
+                }
+            }
+        }
+
+        /// Whether the future has been woken since it was last polled.
+        pub fn woken(&self) -> bool {
+            self.wakes.count() != self.seen
+        }
+
+        /// The number of times the future has been polled.
+        pub fn polls(&self) -> usize {
+            self.polls
+        }
+    }
+}
+
+/// An event a future under test waits on, opened by the test to simulate a
+/// wakeup from an interrupt or another thread.
+#[derive(Default)]
+pub struct Gate {
+    open: Cell<bool>,
+    waker: Cell<Option<Waker>>,
+}
+
+impl Gate {
+    /// Create a new, closed gate.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            open: Cell::new(false),
+            waker: Cell::new(None),
+        }
+    }
+
+    /// Open the gate, waking the future waiting on it.
+    pub fn open(&self) {
+        self.open.set(true);
+        if let Some(waker) = self.waker.take() {
+            waker.wake();
+        }
+    }
+
+    /// Whether the gate has been opened.
+    pub fn is_open(&self) -> bool {
+        self.open.get()
+    }
+
+    /// Wait for the gate to be opened.
+    pub async fn wait(&self) {
+        core::future::poll_fn(|cx| {
+            if self.open.get() {
+                Poll::Ready(())
+            } else {
+                self.waker.set(Some(cx.waker().clone()));
+                Poll::Pending
+            }
+        })
+        .await;
+    }
+}
+
+cfg_locking! {
+    struct ClockState {
+        now: Duration,
+        delays: WakerSet,
+    }
+
+    /// A [`Clock`] and [`Delay`] where time only passes when the test calls
+    /// [`advance`](MockClock::advance).
+    ///
+    /// `&MockClock` implements [`Delay`], so delays can be waited on while the
+    /// test holds onto the clock to move it forward.
+    ///
+    /// ```rust
+    /// use core::time::Duration;
+    /// use woven::test::{block_on, MockClock};
+    /// use woven::time::{timeout, Elapsed};
+    /// use woven::Join;
+    ///
+    /// let clock = MockClock::new();
+    ///
+    /// let (result, ()) = block_on(
+    ///     (
+    ///         timeout(&mut &clock, Duration::from_secs(60), core::future::pending::<()>()),
+    ///         async { clock.advance(Duration::from_secs(60)) },
+    ///     )
+    ///         .join(),
+    /// );
+    ///
+    /// assert_eq!(result, Err(Elapsed));
+    /// ```
+    pub struct MockClock {
+        state: Mutex<ClockState>,
+    }
+
+    impl MockClock {
+        /// Create a new clock, starting at zero.
+        #[must_use]
+        pub const fn new() -> Self {
+            Self {
+                state: Mutex::new(ClockState {
+                    now: Duration::ZERO,
+                    delays: WakerSet::new(),
+                }),
+            }
+        }
+
+        /// Move time forward, waking any delays waiting on the clock.
+        pub fn advance(&self, duration: Duration) {
+            self.state.lock(|s| {
+                s.now = s.now.saturating_add(duration);
+                s.delays.wake();
+            });
+        }
+    }
+
+    impl Default for MockClock {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    impl Clock for MockClock {
+        fn now(&self) -> Duration {
+            self.state.lock(|s| s.now)
+        }
+    }
+
+    impl Delay for &MockClock {
+        async fn delay(&mut self, duration: Duration) {
+            let deadline = self.now().saturating_add(duration);
+
+            core::future::poll_fn(|cx| {
+                self.state.lock(|s| {
+                    if s.now >= deadline {
+                        Poll::Ready(())
+                    } else {
+                        s.delays.register(cx.waker());
+                        Poll::Pending
+                    }
+                })
+            })
+            .await;
+        }
+    }
+
+    /// Drive a future through every order in which the `N` [`Gate`]s it waits on
+    /// can be opened, passing each order and the future's output to `check`.
+    ///
+    /// For each order the gates are closed and a fresh future is made, which is only
+    /// polled again after being woken, as a real executor would. Opening the
+    /// gates one at a time in that order must complete it, so a lost wakeup in
+    /// the future (or the combinators it's built from) is caught as a panic
+    /// naming the order that exposed it.
+    ///
+    /// ```rust
+    /// use woven::test::{interleavings, Gate, WakeCounter};
+    /// use woven::{Either, Join, Race};
+    ///
+    /// static WAKES: WakeCounter = WakeCounter::new();
+    ///
+    /// let gates = [const { Gate::new() }; 3];
+    /// let [a, b, c] = &gates;
+    ///
+    /// interleavings(
+    ///     &WAKES,
+    ///     &gates,
+    ///     || (a.wait(), (b.wait(), c.wait()).race()).join(),
+    ///     |order, (_, winner)| {
+    ///         let b_first = order.iter().position(|&g| g == 1) < order.iter().position(|&g| g == 2);
+    ///         assert_eq!(winner, if b_first { Either::First(()) } else { Either::Second(()) });
+    ///     },
+    /// );
+    /// ```
+    ///
+    /// Channels can be checked the same way, such as a pipe with room for a single
+    /// byte, where the writer has to wait for the reader to make space.
+    ///
+    /// ```rust
+    /// use woven::channel::pipe::Pipe;
+    /// use woven::test::{interleavings, Gate, WakeCounter};
+    /// use woven::Join;
+    ///
+    /// static WAKES: WakeCounter = WakeCounter::new();
+    ///
+    /// let pipe: Pipe<1> = Pipe::new();
+    /// let gates = [const { Gate::new() }; 3];
+    /// let [a, b, c] = &gates;
+    ///
+    /// interleavings(
+    ///     &WAKES,
+    ///     &gates,
+    ///     || {
+    ///         let (mut writer, mut reader) = (pipe.writer(), pipe.reader());
+    ///         let write = async move {
+    ///             a.wait().await;
+    ///             writer.write(b"x").await;
+    ///             b.wait().await;
+    ///             writer.write(b"y").await;
+    ///         };
+    ///         let read = async move {
+    ///             let mut buf = [0; 2];
+    ///             c.wait().await;
+    ///             let n = reader.read(&mut buf[..1]).await;
+    ///             reader.read(&mut buf[n..]).await;
+    ///             buf
+    ///         };
+    ///         (write, read).join()
+    ///     },
+    ///     |_, ((), buf)| assert_eq!(&buf, b"xy"),
+    /// );
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the future hasn't completed once every gate is open.
+    pub fn interleavings<const N: usize, F: Future>(
+        wakes: &'static WakeCounter,
+        gates: &[Gate; N],
+        mut make: impl FnMut() -> F,
+        mut check: impl FnMut(&[usize; N], F::Output),
+    ) {
+        permutations::<N>(|order| {
+            for gate in gates {
+                gate.open.set(false);
+                gate.waker.set(None);
+            }
+
+            let mut step = core::pin::pin!(Step::new(make(), wakes));
+            let mut result = step.as_mut().run_until_stalled();
+
+            for &gate in order {
+                gates[gate].open();
+                if result.is_pending() && step.woken() {
+                    result = step.as_mut().run_until_stalled();
+                }
+            }
+
+            match result {
+                Poll::Ready(output) => check(order, output),
+                Poll::Pending => panic!("future didn't complete opening the gates in order {order:?}"),
+            }
+        });
+    }
+
+    /// Call `f` with every permutation of `0..N`.
+    fn permutations<const N: usize>(mut f: impl FnMut(&[usize; N])) {
+        // Heap's algorithm, iteratively.
+        let mut order: [usize; N] = core::array::from_fn(|i| i);
+        let mut counters = [0; N];
+        f(&order);
+
+        let mut i = 1;
+        while i < N {
+            if counters[i] < i {
+                order.swap(if i % 2 == 0 { 0 } else { counters[i] }, i);
+                f(&order);
+                counters[i] += 1;
+                i = 1;
+            } else {
+                counters[i] = 0;
+                i += 1;
+            }
+        }
+    }
+}
+
+/// Run a future to completion by polling it in a busy loop.
+///
+/// Needs neither `std` nor an executor, so the crate can be exercised in
+/// on-target tests and emulator runs. Wakeups are ignored, the future is
+/// simply polled again until it completes, so this isn't suitable for anything
+/// but tests.
+///
+/// ```rust
+/// use woven::test::block_on;
+/// use woven::Join;
+///
+/// assert_eq!(block_on((async { 1 }, async { 2 }).join()), (1, 2));
+/// ```
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = core::pin::pin!(future);
+
+    loop {
+        if let Poll::Ready(output) = poll_once(future.as_mut()) {
+            return output;
+        }
+
+        core::hint::spin_loop();
+    }
+}
+
+/// Poll a future once with a waker that does nothing.
+pub fn poll_once<F: Future + ?Sized>(future: Pin<&mut F>) -> Poll<F::Output> {
+    future.poll(&mut Context::from_waker(Waker::noop()))
+}
+
+/// Poll a pinned future once, asserting that it is pending.
+///
+/// ```rust
+/// use woven::assert_pending;
+///
+/// let mut pending = core::pin::pin!(core::future::pending::<()>());
+/// assert_pending!(pending.as_mut());
+/// assert_pending!(pending.as_mut(), "still pending after {} polls", 2);
+/// ```
+#[macro_export]
+macro_rules! assert_pending {
+    ($future: expr $(,)?) => {
+        $crate::assert_pending!($future, "expected the future to be pending, but it was ready")
+    };
+    ($future: expr, $( $arg: tt )+) => {
+        if $crate::test::poll_once($future).is_ready() {
+            ::core::panic!($( $arg )+);
+        }
+    };
+}
+
+/// Poll a pinned future once, asserting that it is ready and evaluating to its
+/// output.
+///
+/// ```rust
+/// use woven::{assert_ready, Join};
+///
+/// let mut joined = core::pin::pin!((async { 1 }, async { 2 }).join());
+/// assert_eq!(assert_ready!(joined.as_mut()), (1, 2));
+/// ```
+#[macro_export]
+macro_rules! assert_ready {
+    ($future: expr $(,)?) => {
+        $crate::assert_ready!($future, "expected the future to be ready, but it was pending")
+    };
+    ($future: expr, $( $arg: tt )+) => {
+        match $crate::test::poll_once($future) {
+            ::core::task::Poll::Ready(output) => output,
+            ::core::task::Poll::Pending => ::core::panic!($( $arg )+),
+        }
+    };
+}
+
+/// Poll a pinned future once, asserting that it is ready with the expected
+/// output.
+///
+/// ```rust
+/// use woven::{assert_ready_eq, Race};
+///
+/// let mut raced = core::pin::pin!((core::future::pending::<u8>(), async { 'b' }).race());
+/// assert_ready_eq!(raced.as_mut(), woven::Either::Second('b'));
+/// ```
+#[macro_export]
+macro_rules! assert_ready_eq {
+    ($future: expr, $expected: expr $(,)?) => {
+        ::core::assert_eq!($crate::assert_ready!($future), $expected)
+    };
+    ($future: expr, $expected: expr, $( $arg: tt )+) => {
+        ::core::assert_eq!($crate::assert_ready!($future), $expected, $( $arg )+)
+    };
+}
@@ -0,0 +1,97 @@
+//! Time based combinators.
+//!
+//! Woven doesn't depend on any particular timer, instead the platform's timer
+//! is provided through the [`Delay`] and [`Clock`] traits.
+
+use core::future::Future;
+use core::time::Duration;
+
+use crate::{Either, Race};
+
+/// An asynchronous delay, provided by the platform's timer.
+pub trait Delay {
+    /// Wait for at least the given duration.
+    fn delay(&mut self, duration: Duration) -> impl Future<Output = ()>;
+}
+
+/// A monotonic clock, provided by the platform's timer.
+pub trait Clock {
+    /// The time elapsed since some fixed point, such as boot.
+    fn now(&self) -> Duration;
+}
+
+/// The error returned when a [`timeout`] elapses before its future completes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Elapsed;
+
+impl core::fmt::Display for Elapsed {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("timeout elapsed")
+    }
+}
+
+impl core::error::Error for Elapsed {}
+
+/// [`Elapsed`], attributed to the [`Named`](crate::Named) future that didn't
+/// complete in time. Returned by [`Named::timeout`](crate::Named::timeout).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct NamedElapsed {
+    /// The label of the future that timed out.
+    pub label: &'static str,
+}
+
+impl From<NamedElapsed> for Elapsed {
+    fn from(_: NamedElapsed) -> Self {
+        Elapsed
+    }
+}
+
+impl core::fmt::Display for NamedElapsed {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "timeout elapsed waiting for {}", self.label)
+    }
+}
+
+impl core::error::Error for NamedElapsed {}
+
+/// Run a future to completion, giving up once `duration` has elapsed.
+///
+/// # Errors
+///
+/// Returns [`Elapsed`] if the delay completes before the future does. If both
+/// complete at the same time, the future's output is returned.
+///
+/// ```rust
+/// use core::time::Duration;
+/// use woven::time::{timeout, Delay, Elapsed};
+///
+/// // A timer that has always already expired.
+/// struct Expired;
+///
+/// impl Delay for Expired {
+///     async fn delay(&mut self, _: Duration) {}
+/// }
+///
+/// cassette::block_on(async {
+///     let done = timeout(&mut Expired, Duration::from_millis(10), async { 1 }).await;
+///     assert_eq!(done, Ok(1));
+///
+///     let stuck = timeout(&mut Expired, Duration::from_millis(10), core::future::pending::<()>()).await;
+///     assert_eq!(stuck, Err(Elapsed));
+/// });
+/// ```
+pub async fn timeout<F: Future>(
+    delay: &mut impl Delay,
+    duration: Duration,
+    future: F,
+) -> Result<F::Output, Elapsed> {
+    match (future, delay.delay(duration)).race().await {
+        Either::First(output) => Ok(output),
+        Either::Second(()) => {
+            log_event!("timeout elapsed");
+            Err(Elapsed)
+        }
+    }
+}
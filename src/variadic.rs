@@ -0,0 +1,100 @@
+//! The combinators for cons-lists of futures, which have no arity limit.
+//!
+//! A cons-list is built with the [`cons!`](crate::cons) macro, and the output
+//! of a join is destructured with [`cons_pat!`](crate::cons_pat). Racing a cons-list produces nested
+//! [`Either`]s, ending in `!` since an empty race never resolves.
+//!
+//! This is an experiment towards retiring the macro-generated tuple impls, and
+//! requires a nightly compiler for the never type.
+//!
+//! ```rust
+//! use woven::{cons, cons_pat, Either, Join, Race};
+//!
+//! cassette::block_on(async {
+//!     let cons_pat![a, b, c] = cons![async { 1 }, async { "two" }, async { 3.0 }].join().await;
+//!     assert_eq!((a, b, c), (1, "two", 3.0));
+//!
+//!     let winner = cons![core::future::pending::<u8>(), async { 'b' }].race().await;
+//!     assert!(matches!(winner, Either::Second(Either::First('b'))));
+//! });
+//! ```
+
+use core::future::Future;
+
+use crate::sealed::Sealed;
+use crate::{Either, Join, Map, Race};
+
+/// A non-empty cons-list, holding its first element and the rest of the list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Cons<H, T>(pub H, pub T);
+
+/// The empty cons-list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Nil;
+
+/// Build a cons-list, `cons![a, b, c]` being `Cons(a, Cons(b, Cons(c, Nil)))`.
+#[macro_export]
+macro_rules! cons {
+    () => { $crate::variadic::Nil };
+    ($head: expr $(, $tail: expr )* $(,)?) => {
+        $crate::variadic::Cons($head, $crate::cons!($( $tail ),*))
+    };
+}
+
+/// Destructure a cons-list, the pattern counterpart of [`cons!`].
+#[macro_export]
+macro_rules! cons_pat {
+    () => { $crate::variadic::Nil };
+    ($head: pat_param $(, $tail: pat_param )* $(,)?) => {
+        $crate::variadic::Cons($head, $crate::cons_pat!($( $tail ),*))
+    };
+}
+
+impl Sealed for Nil {}
+
+impl<H, T> Sealed for Cons<H, T> {}
+
+impl Join for Nil {
+    type Output = Nil;
+
+    fn join(self) -> combinator_future!(Self::Output) {
+        Map::new(core::future::ready(()), |()| Nil)
+    }
+}
+
+impl<H, T> Join for Cons<H, T>
+where
+    H: Future,
+    T: Join,
+{
+    type Output = Cons<H::Output, T::Output>;
+
+    fn join(self) -> combinator_future!(Self::Output) {
+        let Cons(head, tail) = self;
+        Map::new((head, tail.join()).join(), |(head, tail)| Cons(head, tail))
+    }
+}
+
+/// An empty race never resolves.
+impl Race for Nil {
+    type Output = !;
+
+    fn race(self) -> combinator_future!(Self::Output) {
+        Map::new(core::future::pending(), |never: !| never)
+    }
+}
+
+impl<H, T> Race for Cons<H, T>
+where
+    H: Future,
+    T: Race,
+{
+    type Output = Either<H::Output, T::Output>;
+
+    fn race(self) -> combinator_future!(Self::Output) {
+        let Cons(head, tail) = self;
+        (head, tail.race()).race()
+    }
+}
@@ -0,0 +1,52 @@
+//! Interop with JavaScript promises, for use in the browser.
+//!
+//! Promises are turned into futures with [`JsFuture`], which can then be used
+//! in any of the combinators. Going the other way, [`into_promise`] and
+//! [`try_into_promise`] export a combined future to JavaScript.
+//!
+//! ```rust,no_run
+//! use js_sys::Promise;
+//! use wasm_bindgen::JsValue;
+//! use woven::wasm::{try_into_promise, JsFuture};
+//! use woven::Join;
+//!
+//! fn fetch_both(a: Promise, b: Promise) -> Promise {
+//!     try_into_promise(async move {
+//!         let (a, b) = (JsFuture::from(a), JsFuture::from(b)).join().await;
+//!         Ok::<JsValue, JsValue>(js_sys::Array::of2(&a?, &b?).into())
+//!     })
+//! }
+//! ```
+
+use core::future::Future;
+
+use js_sys::Promise;
+use wasm_bindgen::JsValue;
+
+pub use wasm_bindgen_futures::JsFuture;
+
+/// Export a future to JavaScript as a promise that always resolves.
+///
+/// The future is started immediately on the JavaScript event loop.
+pub fn into_promise<F>(future: F) -> Promise
+where
+    F: Future + 'static,
+    F::Output: Into<JsValue>,
+{
+    wasm_bindgen_futures::future_to_promise(async move { Ok(future.await.into()) })
+}
+
+/// Export a fallible future to JavaScript as a promise, which rejects if the
+/// future returns an error.
+///
+/// The future is started immediately on the JavaScript event loop.
+pub fn try_into_promise<F, T, E>(future: F) -> Promise
+where
+    F: Future<Output = Result<T, E>> + 'static,
+    T: Into<JsValue>,
+    E: Into<JsValue>,
+{
+    wasm_bindgen_futures::future_to_promise(async move {
+        future.await.map(Into::into).map_err(Into::into)
+    })
+}